@@ -0,0 +1,195 @@
+use crate::analog::gen::{GeneratorFunction, Node};
+use crate::analog::measure::measure_tone;
+use crate::*;
+use std::convert::TryFrom;
+use std::time::Duration;
+use uom::si::{
+    electric_potential::volt,
+    f64::{Frequency, Time},
+    frequency::hertz,
+    time::second,
+};
+
+/// Periods of the test signal captured at each point of a [DeviceHandle::bode_sweep]
+const PERIODS_PER_CAPTURE: f64 = 8.;
+
+/// Periods of the test signal to wait for the circuit to settle at each point of a [DeviceHandle::bode_sweep]
+const SETTLING_PERIODS: f64 = 10.;
+
+/// How [DeviceHandle::bode_sweep] spaces its points
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SweepScale {
+    Linear,
+    /// Evenly spaced on a log scale, the usual choice for frequency responses
+    Log,
+}
+
+impl SweepScale {
+    /// Frequencies of `points` evenly spaced points from the start to the end of `range` in hertz
+    fn points(self, range: &RangeInclusive<f64>, points: usize) -> Vec<f64> {
+        let (start, end) = (*range.start(), *range.end());
+        (0..points)
+            .map(|i| {
+                let fraction = if points == 1 {
+                    0.
+                } else {
+                    i as f64 / (points - 1) as f64
+                };
+                match self {
+                    SweepScale::Linear => start + (end - start) * fraction,
+                    SweepScale::Log => start * (end / start).powf(fraction),
+                }
+            })
+            .collect()
+    }
+}
+
+make_struct! {
+    /// Response at one frequency of a [DeviceHandle::bode_sweep]
+    BodePoint {
+        frequency: Frequency,
+        gain_db: f64,
+        phase_degrees: f64
+    }
+}
+
+impl DeviceHandle {
+    /// Measure a frequency response by generating a sine wave on waveform generator channel `out_ch`
+    /// and measuring it with oscilloscope channel `in_ch` at `points` frequencies across `range`.
+    ///
+    /// Gain is relative to the generator channel's current carrier amplitude, which must be set beforehand,
+    /// and phase is relative to the generated sine wave.
+    /// At each point, the scope is triggered by the generator starting and waits 10 periods for the circuit
+    /// to settle before capturing, as far as the trigger position range allows. The sample rate is chosen to
+    /// capture 8 periods, or as many as fit at the maximum rate.
+    ///
+    /// The oscilloscope and waveform generator settings are changed and not restored.
+    pub fn bode_sweep(
+        &mut self,
+        out_ch: u32,
+        in_ch: u32,
+        range: RangeInclusive<Frequency>,
+        points: usize,
+        scale: SweepScale,
+    ) -> Result<Vec<BodePoint>, WaveFormsError> {
+        let handle = self.handle.unwrap();
+        let out_count = get_int!(FDwfAnalogOutCount handle)?;
+        if out_ch as c_int >= out_count {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "waveform generator channel {} does not exist, there are {} channels",
+                    out_ch, out_count
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let in_count = get_int!(FDwfAnalogInChannelCount handle)?;
+        if in_ch as c_int >= in_count {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "oscilloscope channel {} does not exist, there are {} channels",
+                    in_ch, in_count
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        let range = range.start().get::<hertz>()..=range.end().get::<hertz>();
+        if *range.start() <= 0. || range.end() < range.start() {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "frequency range {} Hz to {} Hz must be positive and increasing",
+                    range.start(),
+                    range.end()
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(2),
+            });
+        }
+        if points == 0 {
+            return Err(WaveFormsError {
+                reason: "at least one point is needed".to_owned(),
+                error_code: WaveFormsErrorCode::InvalidParameter(3),
+            });
+        }
+
+        let mut generator = WaveformGenerator {
+            device_handle: handle,
+            phantom: std::marker::PhantomData,
+        }
+        .channels()?
+        .swap_remove(out_ch as usize);
+        let amplitude = generator.get_amplitude(Node::Carrier)?.get::<volt>();
+        if amplitude <= 0. {
+            return Err(WaveFormsError {
+                reason: "the waveform generator channel's carrier amplitude must be set".to_owned(),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        generator.enable()?;
+        generator.set_function(GeneratorFunction::Sine)?;
+        generator.set_trigger_source(TriggerSource::None)?;
+
+        let mut scope = Oscilloscope {
+            device_handle: handle,
+            enabled_cache: None,
+            phantom: std::marker::PhantomData,
+        };
+        let mut input = scope.channels()?.swap_remove(in_ch as usize);
+        input.enable()?;
+        scope.set_acquisition_mode(AcquisitionMode::Single)?;
+        scope.set_trigger_source(TriggerSource::try_from(
+            trigsrcAnalogOut1 + out_ch as TRIGSRC,
+        )?)?;
+        scope.set_trigger_auto_timeout(Time::new::<second>(0.))?;
+        let buffer_size = scope.get_sample_buffer_size()?;
+        let max_rate = scope.max_sample_frequency()?.get::<hertz>();
+        let max_position = scope.trigger_positions()?.max.get::<second>();
+
+        let mut results = Vec::with_capacity(points);
+        for frequency in scale.points(&range, points) {
+            generator.set_frequency(Frequency::new::<hertz>(frequency))?;
+            let rate = (frequency * buffer_size as f64 / PERIODS_PER_CAPTURE).min(max_rate);
+            scope.set_sampling_frequency(Frequency::new::<hertz>(rate))?;
+            let rate = scope.get_sampling_frequency()?.get::<hertz>();
+            let duration = buffer_size as f64 / rate;
+            let position = (duration / 2. + SETTLING_PERIODS / frequency).min(max_position);
+            scope.set_trigger_position(Time::new::<second>(position))?;
+            let position = scope.get_trigger_position()?.get::<second>();
+
+            let timeout = Duration::from_secs_f64(duration + position) + Duration::from_secs(1);
+            scope.configure(true, true)?;
+            scope.wait_for(|state, _| state == InstrumentState::Armed, timeout)?;
+            generator.start()?;
+            let captured = scope.wait_until_done(timeout);
+            generator.stop()?;
+            captured?;
+
+            // Only whole periods so the tone is measured exactly
+            let periods = (buffer_size as f64 * frequency / rate).floor().max(1.);
+            let count = ((periods * rate / frequency).round() as usize).min(buffer_size);
+            let samples = input
+                .read_samples(count)?
+                .iter()
+                .map(|sample| sample.get::<volt>())
+                .collect::<Vec<_>>();
+            let (response, phase) = measure_tone(
+                &samples,
+                Frequency::new::<hertz>(rate),
+                Frequency::new::<hertz>(frequency),
+            )
+            .ok_or_else(|| WaveFormsError {
+                reason: format!("the acquisition at {} Hz had no samples", frequency),
+                error_code: WaveFormsErrorCode::Other,
+            })?;
+            // The generated sine is a cosine delayed by 90 degrees at the trigger,
+            // and the buffer starts half its duration before the trigger position
+            let start = position - duration / 2.;
+            let reference = 360. * frequency * start - 90.;
+            results.push(BodePoint {
+                frequency: Frequency::new::<hertz>(frequency),
+                gain_db: 20. * (response / amplitude).log10(),
+                phase_degrees: 180. - (180. - (phase - reference)).rem_euclid(360.),
+            });
+        }
+        Ok(results)
+    }
+}
@@ -1,5 +1,10 @@
 use crate::*;
 use std::os::raw::c_int;
+use uom::si::{
+    electric_potential::volt,
+    f64::{ElectricPotential, Frequency},
+    frequency::hertz,
+};
 
 #[derive(Debug)]
 pub struct WaveformGenerator<'handle> {
@@ -13,6 +18,106 @@ impl<'handle> WaveformGenerator<'handle> {
         call!(FDwfAnalogOutReset self.device_handle, -1)
     }
 
+    /// Turn every channel off: the carrier is disabled, the idle output is [Idle::Disable], and the channels are stopped.
+    ///
+    /// This is meant for safety teardown, i.e. when a script fails partway through.
+    /// The configuration is applied, but the instrument is left stopped rather than running.
+    pub fn all_off(&mut self) -> Result<(), WaveFormsError> {
+        for mut channel in self.channels()? {
+            channel.disable()?;
+            channel.set_idle(Idle::Disable)?;
+        }
+        set_false!(FDwfAnalogOutConfigure self.device_handle, -1)
+    }
+
+    /// Configure a channel's carrier to generate a [SignalPreset] and start it
+    pub fn quick(&mut self, channel: u32, preset: SignalPreset) -> Result<(), WaveFormsError> {
+        let mut channels = self.channels()?;
+        let channel_count = channels.len();
+        let channel = channels
+            .get_mut(channel as usize)
+            .ok_or_else(|| WaveFormsError {
+                reason: format!(
+                    "channel {} does not exist, there are {} channels",
+                    channel, channel_count
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            })?;
+        let (function, frequency, amplitude, offset) = preset.parameters();
+        channel.enable()?;
+        channel.set_function(function)?;
+        channel.set_frequency(Frequency::new::<hertz>(frequency))?;
+        channel.set_amplitude(Node::Carrier, ElectricPotential::new::<volt>(amplitude))?;
+        channel.set_offset(Node::Carrier, ElectricPotential::new::<volt>(offset))?;
+        channel.start()
+    }
+
+    /// Stream mono audio out of a channel at `sample_rate`, see [AudioSink].
+    ///
+    /// Samples are scaled by the carrier's amplitude, which should be set beforehand.
+    pub fn audio_sink(
+        &mut self,
+        channel: u32,
+        sample_rate: Frequency,
+    ) -> Result<AudioSink<'handle>, WaveFormsError> {
+        let channel_count = get_int!(FDwfAnalogOutCount self.device_handle)?;
+        if channel as c_int >= channel_count {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "channel {} does not exist, there are {} channels",
+                    channel, channel_count
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let index = channel as c_int;
+        let mut carrier = Channel {
+            device_handle: self.device_handle,
+            index,
+            phantom: std::marker::PhantomData,
+        };
+        carrier.enable()?;
+        carrier.set_function(GeneratorFunction::Play)?;
+        carrier.set_frequency(sample_rate)?;
+        Ok(AudioSink {
+            device_handle: self.device_handle,
+            index,
+            started: false,
+            underruns: 0,
+            scratch: vec![],
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Shift the carrier of `channel` by `degrees` relative to the carrier of `reference`,
+    /// i.e. 120 and 240 degrees for the second and third channels of a three-phase generator.
+    ///
+    /// `reference` is made the master of `channel` so that they start together.
+    /// Both carriers should have the same frequency for the phase relationship to hold.
+    pub fn set_relative_phase(
+        &mut self,
+        channel: u32,
+        reference: u32,
+        degrees: f64,
+    ) -> Result<(), WaveFormsError> {
+        let mut channels = self.channels()?;
+        if channel as usize >= channels.len() || reference as usize >= channels.len() {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "channel {} or reference {} does not exist, there are {} channels",
+                    channel,
+                    reference,
+                    channels.len()
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let reference_phase = channels[reference as usize].get_phase(Node::Carrier)?;
+        let channel = &mut channels[channel as usize];
+        call!(FDwfAnalogOutMasterSet self.device_handle, channel.index, reference as c_int)?;
+        channel.set_phase(Node::Carrier, (reference_phase + degrees).rem_euclid(360.))
+    }
+
     pub fn channels(&mut self) -> Result<Vec<Channel<'handle>>, WaveFormsError> {
         let channel_count = get_int!(FDwfAnalogOutCount self.device_handle)?;
         Ok((0..channel_count)
@@ -32,7 +137,492 @@ pub struct Channel<'handle> {
 }
 
 impl<'handle> Channel<'handle> {
+    /// Reset the channel's configuration, including all of its nodes.
+    ///
+    /// The SDK can only reset nodes together, there is no way to reset a single node.
+    /// To stop modulating while keeping the carrier configured, disable the [Node::Am] or [Node::Fm] node instead,
+    /// its settings are ignored while it is disabled.
     pub fn reset(&mut self) -> Result<(), WaveFormsError> {
         call!(FDwfAnalogOutReset self.device_handle, self.index)
     }
+
+    pub fn start(&mut self) -> Result<(), WaveFormsError> {
+        set_true!(FDwfAnalogOutConfigure self.device_handle, self.index)
+    }
+
+    pub fn stop(&mut self) -> Result<(), WaveFormsError> {
+        set_false!(FDwfAnalogOutConfigure self.device_handle, self.index)
+    }
+
+    /// Enable the carrier so that the channel outputs its waveform when started
+    pub fn enable(&mut self) -> Result<(), WaveFormsError> {
+        set_true!(FDwfAnalogOutNodeEnableSet self.device_handle, self.index, Node::Carrier.into())
+    }
+
+    pub fn disable(&mut self) -> Result<(), WaveFormsError> {
+        set_false!(FDwfAnalogOutNodeEnableSet self.device_handle, self.index, Node::Carrier.into())
+    }
+
+    /// Whether the carrier is enabled
+    pub fn is_enabled(&self) -> Result<bool, WaveFormsError> {
+        get_bool!(FDwfAnalogOutNodeEnableGet self.device_handle, self.index, Node::Carrier.into())
+    }
+
+    /// Waveform shape of the carrier
+    pub fn set_function(&mut self, func: GeneratorFunction) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogOutNodeFunctionSet self.device_handle, self.index, Node::Carrier.into(), func.into())
+    }
+
+    pub fn get_function(&self) -> Result<GeneratorFunction, WaveFormsError> {
+        use std::convert::TryFrom;
+        get_int!(FDwfAnalogOutNodeFunctionGet self.device_handle, self.index, Node::Carrier.into())
+            .and_then(GeneratorFunction::try_from)
+    }
+
+    /// Functions accepted by [Channel::set_function]
+    pub fn supported_functions(&self) -> Result<SupportedGeneratorFunctions, WaveFormsError> {
+        get_int!(FDwfAnalogOutNodeFunctionInfo self.device_handle, self.index, Node::Carrier.into())
+            .map(SupportedGeneratorFunctions::from)
+    }
+
+    /// Frequency of the carrier
+    pub fn set_frequency(&mut self, freq: Frequency) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogOutNodeFrequencySet self.device_handle, self.index, Node::Carrier.into(), freq.get::<hertz>())
+    }
+
+    pub fn get_frequency(&self) -> Result<Frequency, WaveFormsError> {
+        get_float!(FDwfAnalogOutNodeFrequencyGet self.device_handle, self.index, Node::Carrier.into())
+            .map(Frequency::new::<hertz>)
+    }
+
+    /// Frequencies accepted by [Channel::set_frequency]
+    pub fn frequency_range(&self) -> Result<RangeInclusive<Frequency>, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogOutNodeFrequencyInfo self.device_handle, self.index, Node::Carrier.into(), &mut min, &mut max)?;
+        Ok(Frequency::new::<hertz>(min)..=Frequency::new::<hertz>(max))
+    }
+
+    /// Make [Channel::start] emit exactly `cycles` periods of the carrier's current function and stop,
+    /// by setting the run time to `cycles / frequency` and running it once.
+    ///
+    /// Set the carrier frequency first, this fails with [WaveFormsErrorCode::NotSupported] if it is not set,
+    /// and with [WaveFormsErrorCode::InvalidParameter] if the burst does not fit the channel's run time range.
+    pub fn set_burst(&mut self, cycles: u32) -> Result<(), WaveFormsError> {
+        let frequency = get_float!(FDwfAnalogOutNodeFrequencyGet self.device_handle, self.index, AnalogOutNodeCarrier)?;
+        if frequency <= 0. {
+            return Err(WaveFormsError {
+                reason: "the carrier frequency must be set before a burst".to_owned(),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        let run = cycles as f64 / frequency;
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogOutRunInfo self.device_handle, self.index, &mut min, &mut max)?;
+        // A run time of 0 means run forever, so it is never a valid burst
+        if cycles == 0 || run < min || run > max {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} cycles at {} Hz take {} s, outside of {} s to {} s",
+                    cycles, frequency, run, min, max
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        call!(FDwfAnalogOutRunSet self.device_handle, self.index, run)?;
+        call!(FDwfAnalogOutRepeatSet self.device_handle, self.index, 1)
+    }
+
+    pub fn state(&self) -> Result<InstrumentState, WaveFormsError> {
+        use core::convert::TryFrom;
+        get_int!(FDwfAnalogOutStatus self.device_handle, self.index)
+            .and_then(InstrumentState::try_from)
+    }
+
+    /// Shorthand for [InstrumentState::is_running] on the current state
+    pub fn is_running(&self) -> Result<bool, WaveFormsError> {
+        self.state().map(|state| state.is_running())
+    }
+
+    enum_getter_and_setter! {
+        /// The channel waits for this trigger after [Channel::start] when it is not [TriggerSource::None]
+        trigger_source TriggerSource FDwfAnalogOutTriggerSource device_handle, index
+    }
+
+    /// Trigger sources accepted by [Channel::set_trigger_source], which may be a subset of [DeviceHandle::trigger_sources]
+    pub fn supported_trigger_sources(&self) -> Result<SupportedTriggerSources, WaveFormsError> {
+        get_int!(FDwfAnalogOutTriggerSourceInfo self.device_handle, self.index)
+            .map(SupportedTriggerSources::from)
+    }
+
+    enum_getter_and_setter! {
+        /// Output while the channel is not running
+        idle Idle FDwfAnalogOutIdle device_handle, index
+    }
+
+    pub fn idles(&self) -> Result<SupportedIdles, WaveFormsError> {
+        get_int!(FDwfAnalogOutIdleInfo self.device_handle, self.index).map(SupportedIdles::from)
+    }
+
+    pub fn nodes(&self) -> Result<SupportedNodes, WaveFormsError> {
+        get_int!(FDwfAnalogOutNodeInfo self.device_handle, self.index).map(SupportedNodes::from)
+    }
+
+    /// Where an [Node::Am] or [Node::Fm] node gets its modulating signal from.
+    ///
+    /// The SDK cannot feed a modulation node directly from an analog input. To modulate the carrier
+    /// with a live external signal, capture it with the [Oscilloscope](crate::analog::scope::Oscilloscope)
+    /// and stream the samples with [ModulationSource::Streamed] and [Channel::play_data].
+    ///
+    /// Fails with [WaveFormsErrorCode::NotSupported] for [Node::Carrier], or if the channel does not have the node or source.
+    pub fn set_modulation_source(
+        &mut self,
+        node: Node,
+        source: ModulationSource,
+    ) -> Result<(), WaveFormsError> {
+        if node == Node::Carrier || !self.nodes()?.as_enum_variants().contains(&node) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{:?} node cannot be used for modulation on this channel",
+                    node
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        let function = match source {
+            ModulationSource::Custom => funcCustom,
+            ModulationSource::Streamed => funcPlay,
+        };
+        let functions =
+            get_int!(FDwfAnalogOutNodeFunctionInfo self.device_handle, self.index, node.into())?;
+        if functions & (1 << function) == 0 {
+            return Err(WaveFormsError {
+                reason: format!("{:?} modulation source is not supported", source),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        call!(FDwfAnalogOutNodeEnableSet self.device_handle, self.index, node.into(), 1)?;
+        call!(FDwfAnalogOutNodeFunctionSet self.device_handle, self.index, node.into(), function)
+    }
+
+    /// Stream more samples to a node using [ModulationSource::Streamed], normalized to `-1.0..=1.0`.
+    pub fn play_data(&mut self, node: Node, data: &[f64]) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogOutNodePlayData self.device_handle, self.index, node.into(), data.as_ptr() as *mut f64, data.len() as c_int)
+    }
+
+    /// FIFO status of a node streaming with [Channel::play_data]
+    pub fn play_status(&self, node: Node) -> Result<PlayStatus, WaveFormsError> {
+        let mut free = 0;
+        let mut lost = 0;
+        let mut corrupted = 0;
+        call!(FDwfAnalogOutNodePlayStatus self.device_handle, self.index, node.into(), &mut free, &mut lost, &mut corrupted)?;
+        Ok(PlayStatus {
+            free: free.max(0) as usize,
+            lost: lost.max(0) as usize,
+            corrupted: corrupted.max(0) as usize,
+        })
+    }
+
+    pub fn amplitude_range(
+        &self,
+        node: Node,
+    ) -> Result<RangeInclusive<ElectricPotential>, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogOutNodeAmplitudeInfo self.device_handle, self.index, node.into(), &mut min, &mut max)?;
+        Ok(ElectricPotential::new::<volt>(min)..=ElectricPotential::new::<volt>(max))
+    }
+
+    pub fn set_amplitude(
+        &mut self,
+        node: Node,
+        amplitude: ElectricPotential,
+    ) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogOutNodeAmplitudeSet self.device_handle, self.index, node.into(), amplitude.get::<volt>())
+    }
+
+    /// Like [Channel::set_amplitude], but fails with [WaveFormsErrorCode::InvalidParameter] instead of letting the output clip.
+    ///
+    /// The output swings from `offset - amplitude` to `offset + amplitude`, so both must stay within the rails.
+    /// The rails are taken from the node's amplitude and offset ranges: the largest output is the larger of
+    /// the maximum amplitude and the maximum offset. With a 5 V rail and a 2.5 V offset, the largest valid
+    /// amplitude is `5 V - |2.5 V| = 2.5 V`, which is reported in the error.
+    pub fn set_amplitude_checked(
+        &mut self,
+        amplitude: ElectricPotential,
+        node: Node,
+    ) -> Result<(), WaveFormsError> {
+        let amplitude_range = self.amplitude_range(node)?;
+        let offset_range = self.offset_range(node)?;
+        let offset = self.get_offset(node)?.get::<volt>();
+        let rail = amplitude_range
+            .end()
+            .get::<volt>()
+            .max(offset_range.end().get::<volt>().abs())
+            .max(offset_range.start().get::<volt>().abs());
+        let max_amplitude = (rail - offset.abs())
+            .min(amplitude_range.end().get::<volt>())
+            .max(0.);
+        let value = amplitude.get::<volt>();
+        if value < amplitude_range.start().get::<volt>() || value > max_amplitude {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} V amplitude with a {} V offset must be within {} V to {} V to stay inside the {} V output rail",
+                    value,
+                    offset,
+                    amplitude_range.start().get::<volt>(),
+                    max_amplitude,
+                    rail
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        self.set_amplitude(node, amplitude)
+    }
+
+    pub fn get_amplitude(&self, node: Node) -> Result<ElectricPotential, WaveFormsError> {
+        get_float!(FDwfAnalogOutNodeAmplitudeGet self.device_handle, self.index, node.into())
+            .map(ElectricPotential::new::<volt>)
+    }
+
+    pub fn offset_range(
+        &self,
+        node: Node,
+    ) -> Result<RangeInclusive<ElectricPotential>, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogOutNodeOffsetInfo self.device_handle, self.index, node.into(), &mut min, &mut max)?;
+        Ok(ElectricPotential::new::<volt>(min)..=ElectricPotential::new::<volt>(max))
+    }
+
+    pub fn set_offset(
+        &mut self,
+        node: Node,
+        offset: ElectricPotential,
+    ) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogOutNodeOffsetSet self.device_handle, self.index, node.into(), offset.get::<volt>())
+    }
+
+    pub fn get_offset(&self, node: Node) -> Result<ElectricPotential, WaveFormsError> {
+        get_float!(FDwfAnalogOutNodeOffsetGet self.device_handle, self.index, node.into())
+            .map(ElectricPotential::new::<volt>)
+    }
+
+    /// Phase range in degrees
+    pub fn phase_range(&self, node: Node) -> Result<RangeInclusive<f64>, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogOutNodePhaseInfo self.device_handle, self.index, node.into(), &mut min, &mut max)?;
+        Ok(min..=max)
+    }
+
+    /// Phase in degrees
+    pub fn set_phase(&mut self, node: Node, degrees: f64) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogOutNodePhaseSet self.device_handle, self.index, node.into(), degrees)
+    }
+
+    /// Phase in degrees
+    pub fn get_phase(&self, node: Node) -> Result<f64, WaveFormsError> {
+        get_float!(FDwfAnalogOutNodePhaseGet self.device_handle, self.index, node.into())
+    }
+
+    /// Maximum number of custom data samples for a node, and the node's configured frequency.
+    ///
+    /// The frequency is how many times per second the whole custom data buffer is played,
+    /// so each sample is output at `frequency * data.len()`.
+    pub fn custom_data_limits(&self, node: Node) -> Result<(usize, Frequency), WaveFormsError> {
+        use std::convert::TryFrom;
+        let mut min = 0;
+        let mut max = 0;
+        call!(FDwfAnalogOutNodeDataInfo self.device_handle, self.index, node.into(), &mut min, &mut max)?;
+        let frequency =
+            get_float!(FDwfAnalogOutNodeFrequencyGet self.device_handle, self.index, node.into())?;
+        Ok((
+            usize::try_from(max).unwrap_or(0),
+            Frequency::new::<hertz>(frequency),
+        ))
+    }
+
+    /// Samples for a node's custom waveform, normalized to `-1.0..=1.0`.
+    ///
+    /// The samples are scaled by the node's amplitude and offset.
+    /// See [Channel::custom_data_limits] for how many samples can be used.
+    pub fn set_custom_data(&mut self, node: Node, data: &[f64]) -> Result<(), WaveFormsError> {
+        let (max, _) = self.custom_data_limits(node)?;
+        if data.len() > max {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "got {} samples but the node supports at most {}",
+                    data.len(),
+                    max
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        call!(FDwfAnalogOutNodeDataSet self.device_handle, self.index, node.into(), data.as_ptr() as *mut f64, data.len() as c_int)
+    }
+}
+
+enum_and_support_bitfield! {
+    /// Components of an analog out channel's signal
+    Node c_int {
+        /// The output signal
+        Carrier => AnalogOutNodeCarrier,
+        /// Frequency modulation of the carrier
+        Fm => AnalogOutNodeFM,
+        /// Amplitude modulation of the carrier
+        Am => AnalogOutNodeAM
+    }
+}
+
+enum_and_support_bitfield! {
+    /// Output of an analog out channel while it is not running
+    Idle c_int {
+        /// Output is disconnected
+        Disable => DwfAnalogOutIdleDisable,
+        /// Output is the configured offset
+        Offset => DwfAnalogOutIdleOffset,
+        /// Output is the initial value of the waveform
+        Initial => DwfAnalogOutIdleInitial
+    }
+}
+
+/// See [Channel::set_modulation_source]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ModulationSource {
+    /// Samples set with [Channel::set_custom_data], repeated at the node's frequency
+    Custom,
+    /// Samples streamed from this computer with [Channel::play_data]
+    Streamed,
+}
+
+make_struct! {
+    /// Sample counts of a node's play FIFO from [Channel::play_status]
+    PlayStatus {
+        free: usize,
+        lost: usize,
+        corrupted: usize
+    }
+}
+
+/// Mono audio output through a waveform generator channel's play FIFO, from [WaveformGenerator::audio_sink]
+///
+/// The first [AudioSink::write] fills the device buffer and starts playback. Later writes only push as many
+/// samples as the FIFO has room for, so keep writing the rest to avoid underruns.
+/// Playback stops when this is dropped.
+#[derive(Debug)]
+pub struct AudioSink<'handle> {
+    device_handle: c_int,
+    index: c_int,
+    started: bool,
+    underruns: usize,
+    scratch: Vec<f64>,
+    phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> AudioSink<'handle> {
+    /// Push samples normalized to `-1.0..=1.0`, returning how many were accepted
+    pub fn write(&mut self, samples: &[f32]) -> Result<usize, WaveFormsError> {
+        let carrier = Node::Carrier.into();
+        let accepted = if self.started {
+            let mut free = 0;
+            let mut lost = 0;
+            let mut corrupted = 0;
+            call!(FDwfAnalogOutNodePlayStatus self.device_handle, self.index, carrier, &mut free, &mut lost, &mut corrupted)?;
+            self.underruns += (lost.max(0) + corrupted.max(0)) as usize;
+            let accepted = samples.len().min(free.max(0) as usize);
+            if accepted > 0 {
+                self.fill_scratch(&samples[..accepted]);
+                call!(FDwfAnalogOutNodePlayData self.device_handle, self.index, carrier, self.scratch.as_mut_ptr(), accepted as c_int)?;
+            }
+            accepted
+        } else {
+            let mut min = 0;
+            let mut max = 0;
+            call!(FDwfAnalogOutNodeDataInfo self.device_handle, self.index, carrier, &mut min, &mut max)?;
+            let accepted = samples.len().min(max.max(0) as usize);
+            if accepted == 0 {
+                return Ok(0);
+            }
+            self.fill_scratch(&samples[..accepted]);
+            call!(FDwfAnalogOutNodeDataSet self.device_handle, self.index, carrier, self.scratch.as_mut_ptr(), accepted as c_int)?;
+            set_true!(FDwfAnalogOutConfigure self.device_handle, self.index)?;
+            self.started = true;
+            accepted
+        };
+        Ok(accepted)
+    }
+
+    /// Total number of samples lost or corrupted because the FIFO ran dry, as of the last [AudioSink::write]
+    pub fn underruns(&self) -> usize {
+        self.underruns
+    }
+
+    fn fill_scratch(&mut self, samples: &[f32]) {
+        self.scratch.clear();
+        self.scratch
+            .extend(samples.iter().map(|sample| f64::from(*sample)));
+    }
+}
+
+impl<'handle> Drop for AudioSink<'handle> {
+    fn drop(&mut self) {
+        let _ = set_false!(FDwfAnalogOutConfigure self.device_handle, self.index);
+    }
+}
+
+enum_and_support_bitfield! {
+    /// Waveform shapes a node can generate
+    GeneratorFunction FUNC {
+        Dc => funcDC,
+        Sine => funcSine,
+        Square => funcSquare,
+        Triangle => funcTriangle,
+        RampUp => funcRampUp,
+        RampDown => funcRampDown,
+        Noise => funcNoise,
+        Pulse => funcPulse,
+        Trapezium => funcTrapezium,
+        SinePower => funcSinePower,
+        /// Samples set with [Channel::set_custom_data]
+        Custom => funcCustom,
+        /// Samples streamed with [Channel::play_data]
+        Play => funcPlay
+    }
+}
+
+kebab_case_strings! {
+    GeneratorFunction {
+        Dc, Sine, Square, Triangle, RampUp, RampDown, Noise, Pulse, Trapezium, SinePower, Custom, Play
+    }
+}
+
+/// Common test signals for [WaveformGenerator::quick]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum SignalPreset {
+    /// Sine, 1 kHz, 1 V peak to peak centered on 0 V
+    Sine1kHz1Vpp,
+    /// Square, 1 MHz, 0 V to 3.3 V
+    Square1MHz3V3,
+    /// Ramp up, 100 Hz, -1 V to 1 V
+    Ramp100Hz,
+    /// Triangle, 10 kHz, 2 V peak to peak centered on 0 V
+    Triangle10kHz2Vpp,
+    /// Constant 1 V
+    Dc1V,
+}
+
+impl SignalPreset {
+    /// `(function, frequency in hertz, amplitude in volts, offset in volts)`
+    fn parameters(self) -> (GeneratorFunction, f64, f64, f64) {
+        match self {
+            SignalPreset::Sine1kHz1Vpp => (GeneratorFunction::Sine, 1e3, 0.5, 0.),
+            SignalPreset::Square1MHz3V3 => (GeneratorFunction::Square, 1e6, 1.65, 1.65),
+            SignalPreset::Ramp100Hz => (GeneratorFunction::RampUp, 100., 1., 0.),
+            SignalPreset::Triangle10kHz2Vpp => (GeneratorFunction::Triangle, 1e4, 1., 0.),
+            SignalPreset::Dc1V => (GeneratorFunction::Dc, 0., 0., 1.),
+        }
+    }
 }
@@ -0,0 +1,220 @@
+use crate::*;
+use std::os::raw::c_int;
+use std::time::Duration;
+use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
+
+/// Measures the impedance of a device under test using a waveform generator channel,
+/// both scope channels, and a reference resistor.
+///
+/// # Calibration
+///
+/// Fixture parasitics dominate measurements of small components unless they are compensated for:
+///
+/// 1. [ImpedanceAnalyzer::calibrate_open] with nothing connected
+/// 2. [ImpedanceAnalyzer::calibrate_short] with the fixture shorted
+/// 3. [ImpedanceAnalyzer::calibrate_load] with a known resistor connected (optional)
+///
+/// Open and short compensation is kept by the device until [ImpedanceAnalyzer::reset].
+/// The load correction is only kept in this struct, so it is lost once it is dropped.
+#[derive(Debug)]
+pub struct ImpedanceAnalyzer<'handle> {
+    pub(crate) device_handle: c_int,
+    pub(crate) open: Option<(f64, f64)>,
+    pub(crate) short: Option<(f64, f64)>,
+    pub(crate) load_correction: f64,
+    pub(crate) phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> ImpedanceAnalyzer<'handle> {
+    /// Resets the configuration and calibration
+    pub fn reset(&mut self) -> Result<(), WaveFormsError> {
+        self.open = None;
+        self.short = None;
+        self.load_correction = 1.;
+        call!(FDwfAnalogImpedanceReset self.device_handle)
+    }
+
+    pub fn start(&mut self) -> Result<(), WaveFormsError> {
+        set_true!(FDwfAnalogImpedanceConfigure self.device_handle)
+    }
+
+    pub fn stop(&mut self) -> Result<(), WaveFormsError> {
+        set_false!(FDwfAnalogImpedanceConfigure self.device_handle)
+    }
+
+    /// Fetch a measurement from the device and check the instrument state
+    pub fn fetch(&mut self) -> Result<InstrumentState, WaveFormsError> {
+        use core::convert::TryFrom;
+        get_int!(FDwfAnalogImpedanceStatus self.device_handle).and_then(InstrumentState::try_from)
+    }
+
+    /// Keep fetching from the device until the measurement is [InstrumentState::Done]
+    pub fn wait_until_done(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.wait_until_done_or_cancel(timeout, None)
+    }
+
+    /// Like [ImpedanceAnalyzer::wait_until_done], but another thread can cancel the wait with `token`.
+    ///
+    /// When cancelled, the measurement is stopped and this fails with [WaveFormsErrorCode::Interrupted].
+    pub fn wait_until_done_cancellable(
+        &mut self,
+        timeout: Duration,
+        token: &CancelToken,
+    ) -> Result<(), WaveFormsError> {
+        self.wait_until_done_or_cancel(timeout, Some(token))
+    }
+
+    fn wait_until_done_or_cancel(
+        &mut self,
+        timeout: Duration,
+        token: Option<&CancelToken>,
+    ) -> Result<(), WaveFormsError> {
+        poll_until(
+            self,
+            |analyzer| Ok(analyzer.fetch()? == InstrumentState::Done),
+            ImpedanceAnalyzer::stop,
+            timeout,
+            token,
+            "measurement was not done",
+        )
+    }
+
+    uom_getter_and_setter! {
+        /// Frequency of the stimulus signal
+        frequency Frequency<hertz> FDwfAnalogImpedanceFrequency device_handle
+    }
+
+    uom_getter_and_setter! {
+        /// Amplitude of the stimulus signal
+        amplitude ElectricPotential<volt> FDwfAnalogImpedanceAmplitude device_handle
+    }
+
+    uom_getter_and_setter! {
+        offset ElectricPotential<volt> FDwfAnalogImpedanceOffset device_handle
+    }
+
+    /// Resistance of the reference resistor in ohms
+    pub fn set_reference(&mut self, ohms: f64) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogImpedanceReferenceSet self.device_handle, ohms)
+    }
+
+    /// Resistance of the reference resistor in ohms
+    pub fn get_reference(&self) -> Result<f64, WaveFormsError> {
+        get_float!(FDwfAnalogImpedanceReferenceGet self.device_handle)
+    }
+
+    /// Read a quantity from the last fetched measurement, with the load correction applied
+    pub fn measure(&self, measurement: Measurement) -> Result<f64, WaveFormsError> {
+        let value = self.measure_uncorrected(measurement)?;
+        Ok(match measurement {
+            Measurement::Impedance
+            | Measurement::Resistance
+            | Measurement::Reactance
+            | Measurement::SeriesInductance
+            | Measurement::ParallelInductance => value * self.load_correction,
+            Measurement::Admittance
+            | Measurement::Conductance
+            | Measurement::Susceptance
+            | Measurement::SeriesCapacitance
+            | Measurement::ParallelCapacitance => value / self.load_correction,
+            Measurement::ImpedancePhase
+            | Measurement::AdmittancePhase
+            | Measurement::Dissipation
+            | Measurement::Quality => value,
+        })
+    }
+
+    /// Measure the fixture with nothing connected
+    pub fn calibrate_open(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.open = Some(self.measure_reference(timeout)?);
+        self.apply_compensation()
+    }
+
+    /// Measure the fixture with its terminals shorted together
+    pub fn calibrate_short(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.short = Some(self.measure_reference(timeout)?);
+        self.apply_compensation()
+    }
+
+    /// Measure a known resistance of `reference` ohms connected to the fixture.
+    ///
+    /// This corrects gain error remaining after open and short compensation,
+    /// so it should be done after [ImpedanceAnalyzer::calibrate_open] and [ImpedanceAnalyzer::calibrate_short].
+    pub fn calibrate_load(
+        &mut self,
+        reference: f64,
+        timeout: Duration,
+    ) -> Result<(), WaveFormsError> {
+        self.start()?;
+        self.wait_until_done(timeout)?;
+        let measured = self.measure_uncorrected(Measurement::Impedance)?;
+        if measured == 0. || !measured.is_finite() {
+            return Err(WaveFormsError {
+                reason: format!("measured an impedance of {} ohms for the load", measured),
+                error_code: WaveFormsErrorCode::Other,
+            });
+        }
+        self.load_correction = reference / measured;
+        Ok(())
+    }
+
+    /// Measure resistance and reactance without any compensation
+    fn measure_reference(&mut self, timeout: Duration) -> Result<(f64, f64), WaveFormsError> {
+        call!(FDwfAnalogImpedanceCompReset self.device_handle)?;
+        self.start()?;
+        self.wait_until_done(timeout)?;
+        Ok((
+            self.measure_uncorrected(Measurement::Resistance)?,
+            self.measure_uncorrected(Measurement::Reactance)?,
+        ))
+    }
+
+    /// Compensation can only be set on the device once both open and short have been measured
+    fn apply_compensation(&mut self) -> Result<(), WaveFormsError> {
+        match (self.open, self.short) {
+            (
+                Some((open_resistance, open_reactance)),
+                Some((short_resistance, short_reactance)),
+            ) => {
+                call!(FDwfAnalogImpedanceCompSet self.device_handle, open_resistance, open_reactance, short_resistance, short_reactance)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn measure_uncorrected(&self, measurement: Measurement) -> Result<f64, WaveFormsError> {
+        get_float!(FDwfAnalogImpedanceStatusMeasure self.device_handle, measurement.into())
+    }
+}
+
+enum_only! {
+    /// Quantities derived from an impedance measurement
+    Measurement c_int {
+        /// Ohms
+        Impedance => DwfAnalogImpedanceImpedance,
+        /// Radians
+        ImpedancePhase => DwfAnalogImpedanceImpedancePhase,
+        /// Ohms
+        Resistance => DwfAnalogImpedanceResistance,
+        /// Ohms
+        Reactance => DwfAnalogImpedanceReactance,
+        /// Siemens
+        Admittance => DwfAnalogImpedanceAdmittance,
+        /// Radians
+        AdmittancePhase => DwfAnalogImpedanceAdmittancePhase,
+        /// Siemens
+        Conductance => DwfAnalogImpedanceConductance,
+        /// Siemens
+        Susceptance => DwfAnalogImpedanceSusceptance,
+        /// Farads
+        SeriesCapacitance => DwfAnalogImpedanceSeriesCapactance,
+        /// Farads
+        ParallelCapacitance => DwfAnalogImpedanceParallelCapacitance,
+        /// Henries
+        SeriesInductance => DwfAnalogImpedanceSeriesInductance,
+        /// Henries
+        ParallelInductance => DwfAnalogImpedanceParallelInductance,
+        Dissipation => DwfAnalogImpedanceDissipation,
+        Quality => DwfAnalogImpedanceQuality
+    }
+}
@@ -0,0 +1,312 @@
+use crate::*;
+use std::os::raw::{c_char, c_int};
+use uom::si::{
+    electric_current::ampere,
+    electric_potential::volt,
+    f64::{ElectricCurrent, ElectricPotential, Power, ThermodynamicTemperature},
+    power::watt,
+    thermodynamic_temperature::degree_celsius,
+};
+
+/// Power supplies, voltage references, and other analog I/O of a device.
+///
+/// Each [Channel] has nodes, i.e. the enable, voltage, and current of a power supply.
+/// Changes are applied with [AnalogIo::configure], and readings are updated with [AnalogIo::status].
+#[derive(Debug)]
+pub struct AnalogIo<'handle> {
+    pub(crate) device_handle: c_int,
+    pub(crate) phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> AnalogIo<'handle> {
+    pub fn reset(&mut self) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogIOReset self.device_handle)
+    }
+
+    /// Apply node values to the device
+    pub fn configure(&mut self) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogIOConfigure self.device_handle)
+    }
+
+    /// Read node statuses from the device
+    pub fn status(&mut self) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogIOStatus self.device_handle)
+    }
+
+    /// Enables or disables all the device's power supplies
+    pub fn set_master_enable(&mut self, enable: bool) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogIOEnableSet self.device_handle, enable as c_int)
+    }
+
+    pub fn get_master_enable(&self) -> Result<bool, WaveFormsError> {
+        get_bool!(FDwfAnalogIOEnableGet self.device_handle)
+    }
+
+    pub fn channels(&mut self) -> Result<Vec<Channel<'handle>>, WaveFormsError> {
+        let channel_count = get_int!(FDwfAnalogIOChannelCount self.device_handle)?;
+        Ok((0..channel_count)
+            .map(|channel_index| Channel {
+                device_handle: self.device_handle,
+                index: channel_index,
+                phantom: std::marker::PhantomData,
+            })
+            .collect::<Vec<_>>())
+    }
+
+    /// Set the Digital Discovery's digital I/O voltage (VIO), i.e. 1.8V to interface with 1.8V logic.
+    ///
+    /// Fails with [WaveFormsErrorCode::NotSupported] on devices without adjustable VIO.
+    pub fn set_digital_voltage(&mut self, v: ElectricPotential) -> Result<(), WaveFormsError> {
+        self.set_named_node(Some("VIO"), "Voltage", v)
+    }
+
+    /// Set the Digital Discovery's digital input logic threshold.
+    ///
+    /// Fails with [WaveFormsErrorCode::NotSupported] on devices without an adjustable threshold.
+    pub fn set_input_threshold(&mut self, v: ElectricPotential) -> Result<(), WaveFormsError> {
+        self.set_named_node(None, "Threshold", v)
+    }
+
+    /// Reading of a [AnalogIoChannelType::Voltage] node, as of the last [AnalogIo::status]
+    pub fn read_voltage(
+        &self,
+        channel: u32,
+        node: u32,
+    ) -> Result<ElectricPotential, WaveFormsError> {
+        self.read_typed_node(channel, node, AnalogIoChannelType::Voltage)
+            .map(ElectricPotential::new::<volt>)
+    }
+
+    /// Reading of a [AnalogIoChannelType::Current] node, as of the last [AnalogIo::status]
+    pub fn read_current(&self, channel: u32, node: u32) -> Result<ElectricCurrent, WaveFormsError> {
+        self.read_typed_node(channel, node, AnalogIoChannelType::Current)
+            .map(ElectricCurrent::new::<ampere>)
+    }
+
+    /// Reading of a [AnalogIoChannelType::Power] node, as of the last [AnalogIo::status]
+    pub fn read_power(&self, channel: u32, node: u32) -> Result<Power, WaveFormsError> {
+        self.read_typed_node(channel, node, AnalogIoChannelType::Power)
+            .map(Power::new::<watt>)
+    }
+
+    /// Reading of a [AnalogIoChannelType::Temperature] node, as of the last [AnalogIo::status]
+    pub fn read_temperature(
+        &self,
+        channel: u32,
+        node: u32,
+    ) -> Result<ThermodynamicTemperature, WaveFormsError> {
+        self.read_typed_node(channel, node, AnalogIoChannelType::Temperature)
+            .map(ThermodynamicTemperature::new::<degree_celsius>)
+    }
+
+    /// Read a node's status after checking that the channel exists and the node has the expected type
+    fn read_typed_node(
+        &self,
+        channel: u32,
+        node: u32,
+        expected: AnalogIoChannelType,
+    ) -> Result<f64, WaveFormsError> {
+        let channel_count = get_int!(FDwfAnalogIOChannelCount self.device_handle)?;
+        if channel as c_int >= channel_count {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "channel {} does not exist, there are {} channels",
+                    channel, channel_count
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let channel = Channel {
+            device_handle: self.device_handle,
+            index: channel as c_int,
+            phantom: std::marker::PhantomData,
+        };
+        if node >= channel.node_count()? {
+            return Err(WaveFormsError {
+                reason: format!("node {} does not exist", node),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        let node_type = channel.node_type(node)?;
+        if node_type != expected {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "node {} is a {:?} node, not {:?}",
+                    node, node_type, expected
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        channel.node_status(node)
+    }
+
+    /// Find a node by name, optionally in a channel with the given name or label, check the value against its range, and apply it
+    fn set_named_node(
+        &mut self,
+        channel_name: Option<&str>,
+        node_name: &str,
+        v: ElectricPotential,
+    ) -> Result<(), WaveFormsError> {
+        let mut found = None;
+        for channel in self.channels()? {
+            if let Some(channel_name) = channel_name {
+                let (name, label) = channel.name()?;
+                if !name.eq_ignore_ascii_case(channel_name)
+                    && !label.eq_ignore_ascii_case(channel_name)
+                {
+                    continue;
+                }
+            }
+            for node in 0..channel.node_count()? {
+                let (name, _) = channel.node_name(node)?;
+                if name.to_lowercase().contains(&node_name.to_lowercase()) {
+                    found = Some((channel, node));
+                    break;
+                }
+            }
+            if found.is_some() {
+                break;
+            }
+        }
+        let (mut channel, node) = found.ok_or_else(|| WaveFormsError {
+            reason: format!("device does not have an analog I/O {} node", node_name),
+            error_code: WaveFormsErrorCode::NotSupported,
+        })?;
+        let (min, max, _) = channel.node_range(node)?;
+        let value = v.get::<volt>();
+        if !(min..=max).contains(&value) {
+            return Err(WaveFormsError {
+                reason: format!("{} V is outside of {} V to {} V", value, min, max),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        channel.set_node(node, value)?;
+        self.configure()
+    }
+}
+
+#[derive(Debug)]
+pub struct Channel<'handle> {
+    device_handle: c_int,
+    index: c_int,
+    phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> Channel<'handle> {
+    /// The channel's `(name, label)`, i.e. `("Positive Supply", "V+")`
+    pub fn name(&self) -> Result<(String, String), WaveFormsError> {
+        let mut name = [0 as c_char; 32];
+        let mut label = [0 as c_char; 16];
+        call!(FDwfAnalogIOChannelName self.device_handle, self.index, &mut name, &mut label)?;
+        Ok((c_chars_to_string(&name), c_chars_to_string(&label)))
+    }
+
+    pub fn node_count(&self) -> Result<u32, WaveFormsError> {
+        get_int!(FDwfAnalogIOChannelInfo self.device_handle, self.index).map(|x| x.max(0) as u32)
+    }
+
+    /// The node's `(name, units)`, i.e. `("Voltage", "V")`
+    pub fn node_name(&self, node: u32) -> Result<(String, String), WaveFormsError> {
+        let mut name = [0 as c_char; 32];
+        let mut units = [0 as c_char; 16];
+        call!(FDwfAnalogIOChannelNodeName self.device_handle, self.index, node as c_int, &mut name, &mut units)?;
+        Ok((c_chars_to_string(&name), c_chars_to_string(&units)))
+    }
+
+    /// What the node controls or measures, which determines its units
+    pub fn node_type(&self, node: u32) -> Result<AnalogIoChannelType, WaveFormsError> {
+        use std::convert::TryFrom;
+        get_int!(FDwfAnalogIOChannelNodeInfo self.device_handle, self.index, node as c_int)
+            .and_then(AnalogIoChannelType::try_from)
+    }
+
+    /// The `(min, max, steps)` values a node can be set to
+    pub fn node_range(&self, node: u32) -> Result<(f64, f64, u32), WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        let mut steps = 0;
+        call!(FDwfAnalogIOChannelNodeSetInfo self.device_handle, self.index, node as c_int, &mut min, &mut max, &mut steps)?;
+        Ok((min, max, steps.max(0) as u32))
+    }
+
+    /// Set a node's value in its units. Applied with [AnalogIo::configure].
+    pub fn set_node(&mut self, node: u32, value: f64) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogIOChannelNodeSet self.device_handle, self.index, node as c_int, value)
+    }
+
+    pub fn get_node(&self, node: u32) -> Result<f64, WaveFormsError> {
+        get_float!(FDwfAnalogIOChannelNodeGet self.device_handle, self.index, node as c_int)
+    }
+
+    /// A node's reading in its units, as of the last [AnalogIo::status]
+    pub fn node_status(&self, node: u32) -> Result<f64, WaveFormsError> {
+        get_float!(FDwfAnalogIOChannelNodeStatus self.device_handle, self.index, node as c_int)
+    }
+}
+
+enum_only! {
+    /// Type of an analog I/O node from [Channel::node_type].
+    ///
+    /// [AnalogIoChannelType::Voltage] nodes are in volts and read with [AnalogIo::read_voltage],
+    /// [AnalogIoChannelType::Current] nodes are in amperes and read with [AnalogIo::read_current],
+    /// [AnalogIoChannelType::Power] nodes are in watts and read with [AnalogIo::read_power],
+    /// and [AnalogIoChannelType::Temperature] nodes are in degrees Celsius and read with [AnalogIo::read_temperature].
+    /// Other nodes are in the units reported by [Channel::node_name].
+    AnalogIoChannelType ANALOGIO {
+        Enable => analogioEnable,
+        Voltage => analogioVoltage,
+        Current => analogioCurrent,
+        Power => analogioPower,
+        Temperature => analogioTemperature,
+        /// Digital multimeter mode of the Analog Discovery Pro
+        Dmm => analogioDmm,
+        Range => analogioRange,
+        Measure => analogioMeasure,
+        Time => analogioTime,
+        Frequency => analogioFrequency,
+        Resistance => analogioResistance
+    }
+}
+
+make_struct! {
+    /// Device temperature from [DeviceHandle::thermal_status]
+    ThermalStatus {
+        temperature: ThermodynamicTemperature
+    }
+}
+
+impl DeviceHandle {
+    /// Read the device temperature from its analog I/O temperature node.
+    ///
+    /// The SDK does not report whether the device is throttling, so long captures should back off based on the temperature.
+    /// Fails with [WaveFormsErrorCode::NotSupported] on devices without a temperature node.
+    pub fn thermal_status(&self) -> Result<ThermalStatus, WaveFormsError> {
+        let mut io = AnalogIo {
+            device_handle: self.handle.unwrap(),
+            phantom: std::marker::PhantomData,
+        };
+        for channel in io.channels()? {
+            for node in 0..channel.node_count()? {
+                if matches!(
+                    channel.node_type(node),
+                    Ok(AnalogIoChannelType::Temperature)
+                ) {
+                    io.status()?;
+                    return Ok(ThermalStatus {
+                        temperature: io.read_temperature(channel.index as u32, node)?,
+                    });
+                }
+            }
+        }
+        Err(WaveFormsError {
+            reason: "device does not have a temperature node".to_owned(),
+            error_code: WaveFormsErrorCode::NotSupported,
+        })
+    }
+}
+
+fn c_chars_to_string(chars: &[c_char]) -> String {
+    unsafe { CStr::from_ptr(chars.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,86 @@
+use uom::si::{
+    f64::{Frequency, Time},
+    frequency::hertz,
+    time::second,
+};
+
+/// Fraction of time a square wave spends above `threshold`, from `0.0` to `1.0`.
+///
+/// Only whole periods between the first and last rising edges are measured,
+/// so at least two rising edges are needed.
+pub fn measure_duty_cycle(samples: &[f64], threshold: f64) -> Option<f64> {
+    let rising_edges = (1..samples.len())
+        .filter(|&i| samples[i - 1] <= threshold && samples[i] > threshold)
+        .collect::<Vec<_>>();
+    let (first, last) = match rising_edges.as_slice() {
+        [first, .., last] => (*first, *last),
+        _ => return None,
+    };
+    let high = samples[first..last]
+        .iter()
+        .filter(|&&sample| sample > threshold)
+        .count();
+    Some(high as f64 / (last - first) as f64)
+}
+
+/// Time taken by the first rising edge to go from `low_pct` to `high_pct` percent of the signal's swing, i.e. 10% to 90%.
+///
+/// Crossing times are linearly interpolated between samples.
+pub fn measure_rise_time(
+    samples: &[f64],
+    sample_rate: Frequency,
+    low_pct: f64,
+    high_pct: f64,
+) -> Option<Time> {
+    if !(0. ..high_pct).contains(&low_pct) || high_pct > 100. {
+        return None;
+    }
+    let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max <= min {
+        return None;
+    }
+    let low = min + (max - min) * low_pct / 100.;
+    let high = min + (max - min) * high_pct / 100.;
+    let crossing =
+        |i: usize, level: f64| i as f64 + (level - samples[i]) / (samples[i + 1] - samples[i]);
+    let mut low_crossing = None;
+    for i in 0..samples.len() - 1 {
+        if samples[i] <= low && samples[i + 1] > low {
+            low_crossing = Some(crossing(i, low));
+        }
+        if let Some(start) = low_crossing {
+            if samples[i] < high && samples[i + 1] >= high {
+                let end = crossing(i, high);
+                return Some(Time::new::<second>(
+                    (end - start) / sample_rate.get::<hertz>(),
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// Amplitude and phase of the `frequency` component of `samples`, as `(peak volts, degrees)` of a cosine starting at the first sample.
+///
+/// This is a single bin of a DFT, so it is exact when `samples` spans a whole number of periods.
+/// Returns [None] if there are no samples.
+pub fn measure_tone(
+    samples: &[f64],
+    sample_rate: Frequency,
+    frequency: Frequency,
+) -> Option<(f64, f64)> {
+    if samples.is_empty() {
+        return None;
+    }
+    let step = 2. * std::f64::consts::PI * frequency.get::<hertz>() / sample_rate.get::<hertz>();
+    let (re, im) = samples
+        .iter()
+        .enumerate()
+        .fold((0., 0.), |(re, im), (n, sample)| {
+            let (sin, cos) = (step * n as f64).sin_cos();
+            (re + sample * cos, im - sample * sin)
+        });
+    let amplitude = 2. * (re * re + im * im).sqrt() / samples.len() as f64;
+    Some((amplitude, im.atan2(re).to_degrees()))
+}
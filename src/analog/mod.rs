@@ -1,2 +1,7 @@
+pub mod bode;
 pub mod gen;
+pub mod impedance;
+pub mod io;
+pub mod measure;
 pub mod scope;
+pub mod spectrum;
@@ -1,15 +1,46 @@
+use crate::analog::spectrum::{spectrum, Spectrum, SpectrumConfig};
 use crate::*;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
 use std::os::raw::c_int;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use uom::si::{electric_potential::volt, f64::*, frequency::hertz, time::second};
 
+/// Channel enable states known without asking the device, see [Oscilloscope::with_state_cache]
+type EnabledCache = Arc<Mutex<HashMap<c_int, bool>>>;
+
+/// Analog in
+///
+/// # Memory model
+///
+/// Samples are read per channel, and only enabled channels have samples.
+/// In every acquisition mode, each enabled channel has its own buffer of [Oscilloscope::get_sample_buffer_size] samples.
+/// The device's memory is shared by the enabled channels, so enabling fewer of them allows a larger buffer.
+/// In [AcquisitionMode::Record], each fetch makes the same number of new samples available for every enabled channel.
+/// Reading a disabled channel is an error rather than returning another channel's samples.
 #[derive(Debug)]
 pub struct Oscilloscope<'handle> {
     pub(crate) device_handle: c_int,
+    pub(crate) enabled_cache: Option<EnabledCache>,
     pub(crate) phantom: std::marker::PhantomData<&'handle ()>,
 }
 
 impl<'handle> Oscilloscope<'handle> {
+    /// Track channel enable states on this side so [Channel::is_enabled] doesn't need a round trip to the device.
+    ///
+    /// States are learned from [Channel::enable], [Channel::disable], and the first [Channel::is_enabled] of each channel,
+    /// and forgotten on [Oscilloscope::reset].
+    /// The cache assumes nothing else changes the device's settings, i.e. another program or another [Oscilloscope] for the same device.
+    pub fn with_state_cache(mut self) -> Self {
+        self.enabled_cache = Some(Arc::default());
+        self
+    }
+
     pub fn reset(&mut self) -> Result<(), WaveFormsError> {
+        if let Some(cache) = &self.enabled_cache {
+            cache.lock().unwrap().clear();
+        }
         call!(FDwfAnalogInReset self.device_handle)
     }
 
@@ -21,12 +52,63 @@ impl<'handle> Oscilloscope<'handle> {
         set_false!(FDwfAnalogInConfigure self.device_handle, 0)
     }
 
+    /// Send settings to the device when `reconfigure` is set, and start an acquisition when `start` is set.
+    ///
+    /// Leaving `start` unset stops a running acquisition like [Oscilloscope::stop], even when reconfiguring.
+    /// To apply settings changed with [AutoConfigure::Disable] to a running acquisition, set both,
+    /// which restarts the acquisition with the new settings.
+    pub fn configure(&mut self, reconfigure: bool, start: bool) -> Result<(), WaveFormsError> {
+        call!(FDwfAnalogInConfigure self.device_handle, reconfigure as c_int, start as c_int)
+    }
+
     /// Check the instrument state without reading data from the device
     pub fn state(&self) -> Result<InstrumentState, WaveFormsError> {
         use core::convert::TryFrom;
         get_int!(FDwfAnalogInStatus self.device_handle, 0).and_then(InstrumentState::try_from)
     }
 
+    /// Shorthand for [InstrumentState::is_running] on the current state
+    pub fn is_running(&self) -> Result<bool, WaveFormsError> {
+        self.state().map(|state| state.is_running())
+    }
+
+    /// How much of the pre-trigger part of the buffer has been filled, from `0.0` to `1.0`.
+    ///
+    /// This is meaningful while the instrument is in [InstrumentState::Prefill],
+    /// and reflects the last time the state was checked with [Oscilloscope::state] or [Oscilloscope::fetch].
+    /// The pre-trigger part of the buffer is the samples before the trigger in [Oscilloscope::time_axis].
+    pub fn prefill_progress(&self) -> Result<f64, WaveFormsError> {
+        let valid = self.samples_valid()? as f64;
+        let count = self.get_sample_buffer_size()? as f64;
+        let rate = self.get_sampling_frequency()?.get::<hertz>();
+        let position = self.get_trigger_position()?.get::<second>();
+        let required = (count / 2. - position * rate).clamp(0., count);
+        if required == 0. {
+            return Ok(1.);
+        }
+        Ok((valid / required).clamp(0., 1.))
+    }
+
+    /// Where the acquisition is in its lifecycle, with more detail than [Oscilloscope::state].
+    ///
+    /// The state is disambiguated with the valid sample count and [Oscilloscope::prefill_progress],
+    /// which reflect the last time data was fetched with [Oscilloscope::fetch].
+    /// i.e. a [LifecyclePosition::PreTriggerFilling] whose progress doesn't increase between fetches means no samples are arriving.
+    pub fn lifecycle_position(&self) -> Result<LifecyclePosition, WaveFormsError> {
+        Ok(match self.state()? {
+            InstrumentState::Ready | InstrumentState::Config => LifecyclePosition::Idle,
+            InstrumentState::Prefill => LifecyclePosition::PreTriggerFilling {
+                progress: self.prefill_progress()?,
+            },
+            InstrumentState::Armed | InstrumentState::Wait => LifecyclePosition::Armed,
+            InstrumentState::Running => match self.samples_valid()? {
+                0 => LifecyclePosition::Triggered,
+                valid_samples => LifecyclePosition::Acquiring { valid_samples },
+            },
+            InstrumentState::Done => LifecyclePosition::Done,
+        })
+    }
+
     /// Fetch data from the device and check the instrument state
     ///
     /// Samples are read at the `Channel` level.
@@ -35,6 +117,500 @@ impl<'handle> Oscilloscope<'handle> {
         get_int!(FDwfAnalogInStatus self.device_handle, 1).and_then(InstrumentState::try_from)
     }
 
+    /// Status of the last acquisition, as of the last [Oscilloscope::fetch].
+    ///
+    /// This only reads what the fetch brought back from the device, so it must be called after [Oscilloscope::fetch]
+    /// to be meaningful, and all of the fields describe the same fetch.
+    pub fn acquisition_status(&self) -> Result<AcquisitionStatus, WaveFormsError> {
+        let valid_samples = self.samples_valid()?;
+        let samples_left = self.samples_left()?;
+        let write_index = self.write_index()?;
+        let auto_triggered = self.was_auto_triggered()?;
+        let trigger_time = self.acquisition_time()?.to_system_time();
+        let record = self.record_status()?;
+        Ok(AcquisitionStatus {
+            valid_samples,
+            samples_left,
+            write_index,
+            auto_triggered,
+            trigger_time,
+            record_available: record.available,
+            record_lost: record.lost,
+            record_corrupt: record.corrupt,
+        })
+    }
+
+    /// Number of samples acquired so far, as of the last [Oscilloscope::fetch]
+    pub fn samples_valid(&self) -> Result<usize, WaveFormsError> {
+        use std::convert::TryFrom;
+        get_int!(FDwfAnalogInStatusSamplesValid self.device_handle)
+            .map(|x| usize::try_from(x).unwrap_or(0))
+    }
+
+    /// Number of samples left to acquire before the acquisition is done, as of the last [Oscilloscope::fetch]
+    pub fn samples_left(&self) -> Result<usize, WaveFormsError> {
+        use std::convert::TryFrom;
+        get_int!(FDwfAnalogInStatusSamplesLeft self.device_handle)
+            .map(|x| usize::try_from(x).unwrap_or(0))
+    }
+
+    /// When the acquisition was triggered according to the device's clock, as of the last [Oscilloscope::fetch]
+    pub fn acquisition_time(&self) -> Result<AcquisitionTime, WaveFormsError> {
+        let mut seconds = 0;
+        let mut tick_num = 0;
+        let mut tick_den = 0;
+        call!(FDwfAnalogInStatusTime self.device_handle, &mut seconds, &mut tick_num, &mut tick_den)?;
+        Ok(AcquisitionTime {
+            seconds,
+            tick_num,
+            tick_den,
+        })
+    }
+
+    /// Whether the acquisition was started by the [Oscilloscope::set_trigger_auto_timeout] running out
+    /// rather than by a trigger, as of the last [Oscilloscope::fetch]
+    pub fn was_auto_triggered(&self) -> Result<bool, WaveFormsError> {
+        get_bool!(FDwfAnalogInStatusAutoTriggered self.device_handle)
+    }
+
+    /// Buffer position the next sample will be written to in [AcquisitionMode::ScanScreen], as of the last [Oscilloscope::fetch].
+    ///
+    /// The buffer is written circularly, so samples before the index are newer than the ones after it.
+    pub fn write_index(&self) -> Result<usize, WaveFormsError> {
+        use std::convert::TryFrom;
+        get_int!(FDwfAnalogInStatusIndexWrite self.device_handle)
+            .map(|x| usize::try_from(x).unwrap_or(0))
+    }
+
+    /// Sample counts of a [AcquisitionMode::Record] acquisition since the previous [Oscilloscope::fetch].
+    ///
+    /// Nonzero `lost` or `corrupt` counts mean the samples were not fetched quickly enough and the data has gaps.
+    pub fn record_status(&self) -> Result<RecordStatus, WaveFormsError> {
+        record_status(self.device_handle)
+    }
+
+    /// Keep fetching data from the device until the acquisition is [InstrumentState::Done]
+    pub fn wait_until_done(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.wait_until_done_or_cancel(timeout, None)
+    }
+
+    /// Keep fetching data from the device until `predicate` is true for the state and the number of valid samples.
+    ///
+    /// i.e. `scope.wait_for(|_, valid| valid >= 1000, timeout)` waits until at least 1000 samples were taken.
+    pub fn wait_for<F: Fn(InstrumentState, usize) -> bool>(
+        &mut self,
+        predicate: F,
+        timeout: Duration,
+    ) -> Result<(), WaveFormsError> {
+        poll_until(
+            self,
+            |scope| {
+                let state = scope.fetch()?;
+                Ok(predicate(state, scope.samples_valid()?))
+            },
+            Oscilloscope::stop,
+            timeout,
+            None,
+            "condition was not met",
+        )
+    }
+
+    /// Like [Oscilloscope::wait_until_done], but another thread can cancel the wait with `token`.
+    ///
+    /// When cancelled, the acquisition is stopped and this fails with [WaveFormsErrorCode::Interrupted].
+    pub fn wait_until_done_cancellable(
+        &mut self,
+        timeout: Duration,
+        token: &CancelToken,
+    ) -> Result<(), WaveFormsError> {
+        self.wait_until_done_or_cancel(timeout, Some(token))
+    }
+
+    fn wait_until_done_or_cancel(
+        &mut self,
+        timeout: Duration,
+        token: Option<&CancelToken>,
+    ) -> Result<(), WaveFormsError> {
+        poll_until(
+            self,
+            |scope| Ok(scope.fetch()? == InstrumentState::Done),
+            Oscilloscope::stop,
+            timeout,
+            token,
+            "acquisition was not done",
+        )
+    }
+
+    /// Start a single acquisition and wait until it is [InstrumentState::Done]
+    pub fn capture_single(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.capture_single_or_cancel(timeout, None)
+    }
+
+    /// Start a single acquisition and wait until it is [InstrumentState::Done], with a timeout that follows from the settings.
+    ///
+    /// The wait allows for the buffer to fill, the trigger position, and the [Oscilloscope::set_trigger_auto_timeout].
+    /// With a trigger source and no auto timeout, the trigger may never come, so this blocks forever (a timeout of [Duration::MAX])
+    /// until it does; use [Oscilloscope::capture_blocking_cancellable] or [Oscilloscope::capture_single] if that can happen.
+    pub fn capture_blocking(&mut self) -> Result<(), WaveFormsError> {
+        self.capture_blocking_or_cancel(None)
+    }
+
+    /// Like [Oscilloscope::capture_blocking], but another thread can cancel the wait with `token`.
+    ///
+    /// When cancelled, the acquisition is stopped and this fails with [WaveFormsErrorCode::Interrupted].
+    pub fn capture_blocking_cancellable(
+        &mut self,
+        token: &CancelToken,
+    ) -> Result<(), WaveFormsError> {
+        self.capture_blocking_or_cancel(Some(token))
+    }
+
+    fn capture_blocking_or_cancel(
+        &mut self,
+        token: Option<&CancelToken>,
+    ) -> Result<(), WaveFormsError> {
+        let rate = self.get_sampling_frequency()?.get::<hertz>();
+        if rate <= 0. {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "the sampling frequency is {} Hz, so the acquisition would never finish",
+                    rate
+                ),
+                error_code: WaveFormsErrorCode::Other,
+            });
+        }
+        let buffer = self.get_sample_buffer_size()? as f64 / rate;
+        let position = self.get_trigger_position()?.get::<second>().abs();
+        let auto_timeout = self.get_trigger_auto_timeout()?.get::<second>();
+        let waits_for_trigger = self.get_trigger_source()? != TriggerSource::None;
+        let timeout = if waits_for_trigger && auto_timeout <= 0. {
+            Duration::MAX
+        } else {
+            // Allow for the time it takes to configure the device and transfer the data
+            Duration::from_secs_f64(buffer + position + auto_timeout.max(0.))
+                + Duration::from_secs(1)
+        };
+        self.capture_single_or_cancel(timeout, token)
+    }
+
+    fn capture_single_or_cancel(
+        &mut self,
+        timeout: Duration,
+        token: Option<&CancelToken>,
+    ) -> Result<(), WaveFormsError> {
+        self.set_acquisition_mode(AcquisitionMode::Single)?;
+        self.start()?;
+        self.wait_until_done_or_cancel(timeout, token)
+    }
+
+    /// Capture a single acquisition of a channel, returning its samples in volts and the index of the trigger sample.
+    ///
+    /// The index follows from the [Oscilloscope::set_trigger_position] and buffer size like in [Oscilloscope::time_axis].
+    /// It is negative, or at least the number of samples, when the trigger position puts the trigger outside of the buffer.
+    pub fn capture_aligned(
+        &mut self,
+        channel: u32,
+        timeout: Duration,
+    ) -> Result<(Vec<f64>, isize), WaveFormsError> {
+        self.capture_single(timeout)?;
+        let count = self.get_sample_buffer_size()?;
+        let rate = self.get_sampling_frequency()?.get::<hertz>();
+        let position = self.get_trigger_position()?.get::<second>();
+        let trigger_index = (count as f64 / 2. - position * rate).round() as isize;
+        let samples = status_data(self.device_handle, channel as c_int, count)?;
+        Ok((samples, trigger_index))
+    }
+
+    /// Time from the first sample of the last acquisition to where the trigger fired.
+    ///
+    /// The offset is computed from the trigger position the device reports for the acquisition,
+    /// which places the trigger on a sample, so it is always a whole number of sample periods.
+    /// The SDK does not report where the trigger crossing fell between two samples, nor does
+    /// [Oscilloscope::acquisition_time] help, as it timestamps the trigger in base clock ticks
+    /// without relating it to the first sample. Sub-sample trigger offsets are therefore not supported;
+    /// for time-interval and jitter measurements, interpolate the crossing from the samples around it.
+    pub fn trigger_offset(&self) -> Result<Time, WaveFormsError> {
+        let position = get_float!(FDwfAnalogInTriggerPositionStatus self.device_handle)?;
+        let count = self.get_sample_buffer_size()?;
+        let rate = self.get_sampling_frequency()?.get::<hertz>();
+        let trigger_index = (count as f64 / 2. - position * rate).round();
+        Ok(Time::new::<second>(trigger_index / rate))
+    }
+
+    /// Capture `frames` single acquisitions of a channel and average them sample by sample.
+    ///
+    /// This is the average mode found on most oscilloscopes, and reduces uncorrelated noise.
+    /// The acquisition mode is set to [AcquisitionMode::Single] and a trigger source must be set
+    /// so that the frames line up. Each frame must be done within `timeout`.
+    pub fn capture_averaged(
+        &mut self,
+        channel: u32,
+        frames: usize,
+        timeout: Duration,
+    ) -> Result<Vec<f64>, WaveFormsError> {
+        if frames == 0 {
+            return Err(WaveFormsError {
+                reason: "at least one frame is needed to average".to_owned(),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        if self.get_trigger_source()? == TriggerSource::None {
+            return Err(WaveFormsError {
+                reason: "a trigger source is needed so that averaged frames line up".to_owned(),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        self.set_acquisition_mode(AcquisitionMode::Single)?;
+        let count = self.get_sample_buffer_size()?;
+        let mut sums = vec![0.; count];
+        for _ in 0..frames {
+            self.start()?;
+            self.wait_until_done(timeout)?;
+            let samples = status_data(self.device_handle, channel as c_int, count)?;
+            for (sum, sample) in sums.iter_mut().zip(samples) {
+                *sum += sample;
+            }
+        }
+        Ok(sums.into_iter().map(|sum| sum / frames as f64).collect())
+    }
+
+    /// Capture `config.averages` single acquisitions of a channel and compute its [Spectrum].
+    ///
+    /// Like [Oscilloscope::capture_averaged], each frame must be done within `timeout`,
+    /// but no trigger is needed since averaging power doesn't depend on phase.
+    pub fn capture_spectrum(
+        &mut self,
+        channel: u32,
+        config: &SpectrumConfig,
+        timeout: Duration,
+    ) -> Result<Spectrum, WaveFormsError> {
+        if config.averages == 0 {
+            return Err(WaveFormsError {
+                reason: "at least one frame is needed to average".to_owned(),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        self.set_acquisition_mode(AcquisitionMode::Single)?;
+        let count = self.get_sample_buffer_size()?;
+        let mut frames = Vec::with_capacity(config.averages);
+        for _ in 0..config.averages {
+            self.start()?;
+            self.wait_until_done(timeout)?;
+            frames.push(status_data(self.device_handle, channel as c_int, count)?);
+        }
+        spectrum(&frames, self.get_sampling_frequency()?, config).ok_or_else(|| WaveFormsError {
+            reason: "the acquisition had no samples".to_owned(),
+            error_code: WaveFormsErrorCode::Other,
+        })
+    }
+
+    /// Fetch data from the device and read `count` samples in volts from every enabled channel,
+    /// interleaved sample by sample.
+    ///
+    /// Enabled channels are in order of their index, so with channels 0 and 1 enabled the result is
+    /// `ch0[0], ch1[0], ch0[1], ch1[1], ...` like in multi-channel audio formats.
+    /// Disabled channels are left out.
+    pub fn read_interleaved(&mut self, count: usize) -> Result<Vec<f64>, WaveFormsError> {
+        self.fetch()?;
+        let mut channels = vec![];
+        for channel in self.channels()? {
+            if channel.is_enabled()? {
+                channels.push(status_data(self.device_handle, channel.index, count)?);
+            }
+        }
+        Ok((0..count)
+            .flat_map(|i| channels.iter().map(move |samples| samples[i]))
+            .collect())
+    }
+
+    /// Capture a single acquisition of every enabled channel and write it to `writer` as CSV.
+    ///
+    /// The header row is followed by one row per sample with the time relative to the trigger
+    /// (see [Oscilloscope::time_axis]) and each enabled channel's voltage.
+    pub fn capture_to_csv<W: Write>(
+        &mut self,
+        mut writer: W,
+        timeout: Duration,
+    ) -> Result<(), WaveFormsError> {
+        self.capture_single(timeout)?;
+        let time_axis = self.time_axis()?;
+        let mut header = "time (s)".to_owned();
+        let mut channels = vec![];
+        for channel in self.channels()? {
+            if channel.is_enabled()? {
+                header += &format!(",channel {} (V)", channel.index);
+                channels.push(status_data(
+                    self.device_handle,
+                    channel.index,
+                    time_axis.len(),
+                )?);
+            }
+        }
+        let write_error = |err: std::io::Error| WaveFormsError {
+            reason: format!("failed to write CSV: {}", err),
+            error_code: WaveFormsErrorCode::Other,
+        };
+        writeln!(writer, "{}", header).map_err(write_error)?;
+        for (i, time) in time_axis.iter().enumerate() {
+            write!(writer, "{}", time.get::<second>()).map_err(write_error)?;
+            for samples in &channels {
+                write!(writer, ",{}", samples[i]).map_err(write_error)?;
+            }
+            writeln!(writer).map_err(write_error)?;
+        }
+        writer.flush().map_err(write_error)
+    }
+
+    /// Stream a channel into a ring buffer holding the most recent `capacity` samples.
+    ///
+    /// The scope should be started in [AcquisitionMode::Record] before creating the reader. See [RingReader::poll].
+    /// Each poll fetches a chunk of the record that no other reader sees, so the reader borrows the scope mutably
+    /// to make it the only one; use [Oscilloscope::double_buffer] to stream several channels.
+    pub fn ring_reader(&mut self, channel: u32, capacity: usize) -> RingReader<'_> {
+        RingReader {
+            device_handle: self.device_handle,
+            channel: channel as c_int,
+            ring: VecDeque::with_capacity(capacity),
+            capacity,
+            scratch: vec![],
+            dropped: 0,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Stream channels into preallocated buffers, see [DoubleBuffer].
+    ///
+    /// `buffers` holds a `(channel, front, back)` for each channel to read. Every buffer must have the same length,
+    /// which is the most samples a [DoubleBuffer::poll] can return.
+    /// The scope should be started in [AcquisitionMode::Record] before creating the buffers.
+    /// Like [Oscilloscope::ring_reader], it borrows the scope mutably since each poll consumes a chunk of the record.
+    pub fn double_buffer(
+        &mut self,
+        buffers: Vec<(u32, Vec<f64>, Vec<f64>)>,
+    ) -> Result<DoubleBuffer<'_>, WaveFormsError> {
+        let capacity = buffers
+            .first()
+            .map(|(_, front, _)| front.len())
+            .unwrap_or(0);
+        if buffers
+            .iter()
+            .any(|(_, front, back)| front.len() != capacity || back.len() != capacity)
+        {
+            return Err(WaveFormsError {
+                reason: "all buffers must have the same length".to_owned(),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let mut channels = Vec::with_capacity(buffers.len());
+        let mut front = Vec::with_capacity(buffers.len());
+        let mut back = Vec::with_capacity(buffers.len());
+        for (channel, channel_front, channel_back) in buffers {
+            channels.push(channel as c_int);
+            front.push(channel_front);
+            back.push(channel_back);
+        }
+        Ok(DoubleBuffer {
+            device_handle: self.device_handle,
+            channels,
+            front,
+            back,
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Read back the acquisition, trigger, and channel settings so they can be stored and applied later with [Oscilloscope::apply_snapshot]
+    pub fn snapshot(&self) -> Result<OscilloscopeSnapshot, WaveFormsError> {
+        let mut channels = vec![];
+        for channel in self.channel_list()? {
+            channels.push(ChannelSnapshot {
+                index: channel.index as u32,
+                enabled: channel.is_enabled()?,
+                range_volts: channel.get_range()?.get::<volt>(),
+                offset_volts: channel.get_offset()?.get::<volt>(),
+                filter: channel.get_filter()?,
+                attenuation: channel.get_attenuation()?,
+            });
+        }
+        Ok(OscilloscopeSnapshot {
+            sample_rate_hertz: self.get_sampling_frequency()?.get::<hertz>(),
+            buffer_size: self.get_sample_buffer_size()?,
+            acquisition_mode: self.get_acquisition_mode()?,
+            trigger_source: self.get_trigger_source()?,
+            trigger_type: self.get_trigger_type()?,
+            trigger_condition: self.get_trigger_condition()?,
+            trigger_level_volts: self.get_trigger_level()?.get::<volt>(),
+            trigger_hysteresis_volts: self.get_trigger_hysteresis()?.get::<volt>(),
+            trigger_position_seconds: self.get_trigger_position()?.get::<second>(),
+            trigger_auto_timeout_seconds: self.get_trigger_auto_timeout()?.get::<second>(),
+            trigger_holdoff_seconds: self.get_trigger_holdoff()?.get::<second>(),
+            channels,
+        })
+    }
+
+    /// The calibration state of each channel, to record alongside measurements.
+    ///
+    /// The device applies its factory calibration internally and the SDK does not expose the raw gain and offset coefficients,
+    /// so this reports the effective range, offset, and attenuation that samples are scaled with instead.
+    pub fn calibration_info(&self) -> Result<CalibrationInfo, WaveFormsError> {
+        let adc_bits = self.adc_bit_width()?;
+        let channel_count = get_int!(FDwfAnalogInChannelCount self.device_handle)?;
+        let mut channels = vec![];
+        for index in 0..channel_count {
+            let range_volts = get_float!(FDwfAnalogInChannelRangeGet self.device_handle, index)?;
+            channels.push(ChannelCalibration {
+                index: index as u32,
+                range_volts,
+                offset_volts: get_float!(FDwfAnalogInChannelOffsetGet self.device_handle, index)?,
+                attenuation: get_float!(FDwfAnalogInChannelAttenuationGet self.device_handle, index)?,
+                volts_per_code: range_volts / 2f64.powi(adc_bits as i32),
+            });
+        }
+        Ok(CalibrationInfo { adc_bits, channels })
+    }
+
+    /// Apply settings read with [Oscilloscope::snapshot]. Channels are matched by index.
+    pub fn apply_snapshot(
+        &mut self,
+        snapshot: &OscilloscopeSnapshot,
+    ) -> Result<(), WaveFormsError> {
+        self.set_timebase(
+            Frequency::new::<hertz>(snapshot.sample_rate_hertz),
+            snapshot.buffer_size,
+        )?;
+        self.set_acquisition_mode(snapshot.acquisition_mode)?;
+        self.set_trigger_source(snapshot.trigger_source)?;
+        self.set_trigger_type(snapshot.trigger_type)?;
+        self.set_trigger_condition(snapshot.trigger_condition)?;
+        self.set_trigger_level(ElectricPotential::new::<volt>(snapshot.trigger_level_volts))?;
+        self.set_trigger_hysteresis(ElectricPotential::new::<volt>(
+            snapshot.trigger_hysteresis_volts,
+        ))?;
+        self.set_trigger_position(Time::new::<second>(snapshot.trigger_position_seconds))?;
+        self.set_trigger_auto_timeout(Time::new::<second>(snapshot.trigger_auto_timeout_seconds))?;
+        self.set_trigger_holdoff(Time::new::<second>(snapshot.trigger_holdoff_seconds))?;
+        let mut channels = self.channels()?;
+        for channel_snapshot in &snapshot.channels {
+            let channel = channels
+                .get_mut(channel_snapshot.index as usize)
+                .ok_or_else(|| WaveFormsError {
+                    reason: format!("channel {} does not exist", channel_snapshot.index),
+                    error_code: WaveFormsErrorCode::InvalidParameter(0),
+                })?;
+            if channel_snapshot.enabled {
+                channel.enable()?;
+            } else {
+                channel.disable()?;
+            }
+            channel.set_range(ElectricPotential::new::<volt>(channel_snapshot.range_volts))?;
+            channel.set_offset(ElectricPotential::new::<volt>(
+                channel_snapshot.offset_volts,
+            ))?;
+            channel.set_filter(channel_snapshot.filter)?;
+            channel.set_attenuation(channel_snapshot.attenuation)?;
+        }
+        Ok(())
+    }
+
     uom_getter_and_setter! {
         /// When zero, the record will run indefinitely.
         record_length Time<second> FDwfAnalogInRecordLength device_handle
@@ -47,6 +623,47 @@ impl<'handle> Oscilloscope<'handle> {
         sampling_frequency Frequency<hertz> FDwfAnalogInFrequency device_handle
     }
 
+    /// Time of each sample in the buffer relative to the trigger.
+    ///
+    /// The trigger is at the center of the buffer, moved by [Oscilloscope::get_trigger_position].
+    pub fn time_axis(&self) -> Result<Vec<Time>, WaveFormsError> {
+        let count = self.get_sample_buffer_size()?;
+        let rate = self.get_sampling_frequency()?.get::<hertz>();
+        let position = self.get_trigger_position()?.get::<second>();
+        Ok((0..count)
+            .map(|i| Time::new::<second>((i as f64 - count as f64 / 2.) / rate + position))
+            .collect())
+    }
+
+    /// Fetch data from the device and read the first `count` samples of a channel,
+    /// each paired with its time relative to the trigger like in [Oscilloscope::time_axis].
+    pub fn samples_with_time(
+        &mut self,
+        channel: u32,
+        count: usize,
+    ) -> Result<impl Iterator<Item = (Time, ElectricPotential)>, WaveFormsError> {
+        let buffer_size = self.get_sample_buffer_size()?;
+        if count > buffer_size {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} samples requested but the buffer has {}",
+                    count, buffer_size
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        let rate = self.get_sampling_frequency()?.get::<hertz>();
+        let position = self.get_trigger_position()?.get::<second>();
+        self.fetch()?;
+        let samples = status_data(self.device_handle, channel as c_int, count)?;
+        Ok(samples.into_iter().enumerate().map(move |(i, sample)| {
+            (
+                Time::new::<second>((i as f64 - buffer_size as f64 / 2.) / rate + position),
+                ElectricPotential::new::<volt>(sample),
+            )
+        }))
+    }
+
     pub fn max_sample_frequency(&self) -> Result<Frequency, WaveFormsError> {
         let mut min = 0.;
         let mut max = 0.;
@@ -61,11 +678,109 @@ impl<'handle> Oscilloscope<'handle> {
         Ok(Frequency::new::<hertz>(min))
     }
 
+    /// Like [Oscilloscope::min_sample_frequency] and [Oscilloscope::max_sample_frequency] in a single call
+    pub fn sample_frequency_range(&self) -> Result<RangeInclusive<Frequency>, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogInFrequencyInfo self.device_handle, &mut min, &mut max)?;
+        Ok(Frequency::new::<hertz>(min)..=Frequency::new::<hertz>(max))
+    }
+
+    /// Sample frequencies the scope can actually achieve, fastest first.
+    ///
+    /// The ADC always runs at the maximum frequency, so the achievable frequencies are
+    /// the maximum divided by an integer, down to the minimum. At most `max_entries` are returned.
+    pub fn available_sample_rates(
+        &self,
+        max_entries: usize,
+    ) -> Result<Vec<Frequency>, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogInFrequencyInfo self.device_handle, &mut min, &mut max)?;
+        // The divider is bounded in case the SDK reports a minimum of zero
+        Ok((1..=u32::MAX)
+            .map(|divider| max / f64::from(divider))
+            .take_while(|rate| *rate >= min && *rate > 0.)
+            .take(max_entries)
+            .map(Frequency::new::<hertz>)
+            .collect())
+    }
+
+    /// Set the sample frequency and buffer size together, after checking both against their ranges.
+    ///
+    /// Setting one and then the other can be rejected by the device if the intermediate combination is invalid.
+    /// Here, automatic configuration is paused so that both are applied at once.
+    pub fn set_timebase(
+        &mut self,
+        sample_rate: Frequency,
+        buffer_size: usize,
+    ) -> Result<(), WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogInFrequencyInfo self.device_handle, &mut min, &mut max)?;
+        let rate = sample_rate.get::<hertz>();
+        if !(min..=max).contains(&rate) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "sample rate {} Hz is outside of {} Hz to {} Hz",
+                    rate, min, max
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let buffer_sizes = self.sample_buffer_size_range()?;
+        if !buffer_sizes.contains(&buffer_size) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "buffer size {} is outside of {:?}",
+                    buffer_size, buffer_sizes
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        let auto_configure = AutoConfigure::get(self.device_handle)?;
+        AutoConfigure::Disable.set(self.device_handle)?;
+        let result = call!(FDwfAnalogInBufferSizeSet self.device_handle, buffer_size as c_int)
+            .and_then(|_| call!(FDwfAnalogInFrequencySet self.device_handle, rate));
+        auto_configure.set(self.device_handle)?;
+        result?;
+        if auto_configure != AutoConfigure::Disable {
+            apply_settings(self.device_handle)?;
+        }
+        Ok(())
+    }
+
+    /// Resolution of the ADC.
+    ///
+    /// Codes from [Channel::read_samples_raw] are left-aligned to 16 bits, so one ADC step is `2^(16 - bits)` codes.
+    ///
+    /// The SDK reports a single resolution per device that doesn't change with the sample rate.
+    /// Below the maximum sample frequency, the [Filter] decides how ADC samples are combined into stored samples,
+    /// i.e. [Filter::Average] reduces noise but the codes keep this bit width.
     pub fn adc_bit_width(&self) -> Result<u32, WaveFormsError> {
         use std::convert::TryFrom;
         get_int!(FDwfAnalogInBitsInfo self.device_handle).map(|x| u32::try_from(x).unwrap_or(0))
     }
 
+    /// Select the ADC resolution.
+    ///
+    /// The SDK has no resolution selector, so this only succeeds when `bits` is the [Oscilloscope::adc_bit_width]
+    /// and fails with [WaveFormsErrorCode::NotSupported] otherwise.
+    pub fn set_resolution(&mut self, bits: AdcResolution) -> Result<(), WaveFormsError> {
+        let adc_bit_width = self.adc_bit_width()?;
+        if bits.bits() != adc_bit_width {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "the ADC resolution is fixed at {} bits, {} bits is not supported",
+                    adc_bit_width,
+                    bits.bits()
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        Ok(())
+    }
+
     pub fn sample_buffer_size_range(&self) -> Result<RangeInclusive<usize>, WaveFormsError> {
         use std::convert::TryFrom;
         let mut min = 0;
@@ -74,8 +789,13 @@ impl<'handle> Oscilloscope<'handle> {
         Ok(min as usize..=usize::try_from(max).unwrap_or(usize::MAX))
     }
 
-    pub fn set_sample_buffer_size(&mut self, size: usize) -> Result<(), WaveFormsError> {
-        call!(FDwfAnalogInBufferSizeSet self.device_handle, size as c_int)
+    /// Request a buffer size, returning the size the device actually applied.
+    ///
+    /// The device may round `size` to one it supports, so size sample reads with the returned value
+    /// rather than the requested one. [Oscilloscope::get_sample_buffer_size] also returns the applied size.
+    pub fn set_sample_buffer_size(&mut self, size: usize) -> Result<usize, WaveFormsError> {
+        call!(FDwfAnalogInBufferSizeSet self.device_handle, size as c_int)?;
+        self.get_sample_buffer_size()
     }
 
     pub fn get_sample_buffer_size(&self) -> Result<usize, WaveFormsError> {
@@ -106,6 +826,24 @@ impl<'handle> Oscilloscope<'handle> {
             .map(SupportedAcquisitionModes::from)
     }
 
+    /// Like [Oscilloscope::set_acquisition_mode], but fails with [WaveFormsErrorCode::NotSupported] if the device does not support the mode
+    pub fn set_acquisition_mode_checked(
+        &mut self,
+        mode: AcquisitionMode,
+    ) -> Result<(), WaveFormsError> {
+        let supported = self.acquisition_modes()?.as_enum_variants();
+        if !supported.contains(&mode) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{:?} acquisition mode is not supported, choose from {:?}",
+                    mode, supported
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        self.set_acquisition_mode(mode)
+    }
+
     enum_getter_and_setter! {
         sampling_source TriggerSource FDwfAnalogInSamplingSource device_handle
     }
@@ -119,11 +857,17 @@ impl<'handle> Oscilloscope<'handle> {
     }
 
     pub fn channels(&mut self) -> Result<Vec<Channel<'handle>>, WaveFormsError> {
+        self.channel_list()
+    }
+
+    /// Like [Oscilloscope::channels], for methods that only read channel settings
+    fn channel_list(&self) -> Result<Vec<Channel<'handle>>, WaveFormsError> {
         let channel_count = get_int!(FDwfAnalogInChannelCount self.device_handle)?;
         Ok((0..channel_count)
             .map(|channel_index| Channel {
                 device_handle: self.device_handle,
                 index: channel_index,
+                enabled_cache: self.enabled_cache.clone(),
                 phantom: std::marker::PhantomData,
             })
             .collect::<Vec<_>>())
@@ -134,9 +878,21 @@ impl<'handle> Oscilloscope<'handle> {
     }
 
     enum_getter_and_setter! {
+        /// The trigger level, hysteresis, type, etc. only apply to [TriggerSource::DetectorAnalogIn].
+        ///
+        /// The external trigger inputs ([TriggerSource::External] and so on) are digital inputs
+        /// with a fixed logic threshold, which the SDK has no call to change.
+        /// To trigger on an external analog signal at a particular threshold, connect it to a scope channel
+        /// and use [TriggerSource::DetectorAnalogIn] with [Oscilloscope::set_trigger_level].
         trigger_source TriggerSource FDwfAnalogInTriggerSource device_handle
     }
 
+    /// Trigger sources accepted by [Oscilloscope::set_trigger_source], which may be a subset of [DeviceHandle::trigger_sources]
+    pub fn supported_trigger_sources(&self) -> Result<SupportedTriggerSources, WaveFormsError> {
+        get_int!(FDwfAnalogInTriggerSourceInfo self.device_handle)
+            .map(SupportedTriggerSources::from)
+    }
+
     pub fn trigger_positions(&self) -> Result<Steps<Time>, WaveFormsError> {
         let mut min = 0.;
         let mut max = 0.;
@@ -169,6 +925,29 @@ impl<'handle> Oscilloscope<'handle> {
         trigger_auto_timeout Time<second> FDwfAnalogInTriggerAutoTimeout device_handle
     }
 
+    /// Set up triggering like the auto, normal, and single buttons of a scope, see [ScopeTriggerMode].
+    ///
+    /// This sets the [Oscilloscope::set_trigger_auto_timeout] and [Oscilloscope::set_acquisition_mode].
+    pub fn set_trigger_mode(&mut self, mode: ScopeTriggerMode) -> Result<(), WaveFormsError> {
+        let (timeout, acquisition_mode) = match mode {
+            ScopeTriggerMode::Auto { timeout } => {
+                if timeout <= Time::new::<second>(0.) {
+                    return Err(WaveFormsError {
+                        reason: "auto trigger timeout must be more than zero".to_owned(),
+                        error_code: WaveFormsErrorCode::InvalidParameter(0),
+                    });
+                }
+                (timeout, AcquisitionMode::Single)
+            }
+            ScopeTriggerMode::Normal => (Time::new::<second>(0.), AcquisitionMode::Single),
+            ScopeTriggerMode::Single => {
+                (Time::new::<second>(0.), AcquisitionMode::SingleWithoutRearm)
+            }
+        };
+        self.set_trigger_auto_timeout(timeout)?;
+        self.set_acquisition_mode(acquisition_mode)
+    }
+
     pub fn trigger_holdoffs(&self) -> Result<Steps<Time>, WaveFormsError> {
         let mut min = 0.;
         let mut max = 0.;
@@ -200,6 +979,38 @@ impl<'handle> Oscilloscope<'handle> {
         get_int!(FDwfAnalogInTriggerFilterInfo self.device_handle).map(SupportedFilters::from)
     }
 
+    /// Like [Oscilloscope::set_trigger_filter], but checks the filter against [Oscilloscope::trigger_filters]
+    /// and the current [Oscilloscope::get_trigger_type].
+    ///
+    /// The trigger detector sees filtered samples:
+    ///
+    /// * [Filter::Decimate] works with every trigger type
+    /// * [Filter::Average] suits [TriggerType::Edge] and [TriggerType::Window] triggers on noisy signals
+    /// * [Filter::MinMax] keeps short pulses visible for [TriggerType::Pulse] and [TriggerType::Window] triggers.
+    ///   It is rejected for [TriggerType::Edge] because consecutive minimum and maximum samples look like edges.
+    ///
+    /// Fails with [WaveFormsErrorCode::NotSupported] if the combination is invalid.
+    pub fn set_trigger_filter_checked(&mut self, filter: Filter) -> Result<(), WaveFormsError> {
+        let supported = self.trigger_filters()?.as_enum_variants();
+        if !supported.contains(&filter) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{:?} trigger filter is not supported, choose from {:?}",
+                    filter, supported
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        let trigger_type = self.get_trigger_type()?;
+        if filter == Filter::MinMax && trigger_type == TriggerType::Edge {
+            return Err(WaveFormsError {
+                reason: "MinMax trigger filter is unreliable with an Edge trigger, use Decimate or Average".to_owned(),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        self.set_trigger_filter(filter)
+    }
+
     enum_getter_and_setter! {
         trigger_condition SamplingSlope FDwfAnalogInTriggerCondition device_handle
     }
@@ -222,6 +1033,7 @@ impl<'handle> Oscilloscope<'handle> {
     }
 
     uom_getter_and_setter! {
+        /// Level for the analog in detector. This has no effect on the external trigger inputs.
         trigger_level ElectricPotential<volt> FDwfAnalogInTriggerLevel device_handle
     }
 
@@ -267,6 +1079,319 @@ impl<'handle> Oscilloscope<'handle> {
     }
 }
 
+/// Read the first `count` samples of a channel in volts, as of the last [Oscilloscope::fetch].
+///
+/// Fails if the channel is disabled, where the SDK would return another channel's samples.
+fn status_data(
+    device_handle: c_int,
+    channel: c_int,
+    count: usize,
+) -> Result<Vec<f64>, WaveFormsError> {
+    ensure_enabled(device_handle, channel)?;
+    let mut samples = vec![0.; count];
+    call!(FDwfAnalogInStatusData device_handle, channel, samples.as_mut_ptr(), count as c_int)?;
+    Ok(samples)
+}
+
+/// Send the oscilloscope's settings to the device, restarting the acquisition if it is running so that it keeps running
+pub(crate) fn apply_settings(device_handle: c_int) -> Result<(), WaveFormsError> {
+    use core::convert::TryFrom;
+    let running = get_int!(FDwfAnalogInStatus device_handle, 0)
+        .and_then(InstrumentState::try_from)?
+        .is_running();
+    call!(FDwfAnalogInConfigure device_handle, 1, running as c_int)
+}
+
+/// See [Oscilloscope::record_status]
+fn record_status(device_handle: c_int) -> Result<RecordStatus, WaveFormsError> {
+    let mut available = 0;
+    let mut lost = 0;
+    let mut corrupt = 0;
+    call!(FDwfAnalogInStatusRecord device_handle, &mut available, &mut lost, &mut corrupt)?;
+    Ok(RecordStatus {
+        available: available.max(0) as usize,
+        lost: lost.max(0) as usize,
+        corrupt: corrupt.max(0) as usize,
+    })
+}
+
+/// Fail with [WaveFormsErrorCode::InvalidParameter] if a channel is not enabled, since it has no samples
+fn ensure_enabled(device_handle: c_int, channel: c_int) -> Result<(), WaveFormsError> {
+    if !get_bool!(FDwfAnalogInChannelEnableGet device_handle, channel)? {
+        return Err(WaveFormsError {
+            reason: format!("channel {} is not enabled, so it has no samples", channel),
+            error_code: WaveFormsErrorCode::InvalidParameter(0),
+        });
+    }
+    Ok(())
+}
+
+make_struct! {
+    /// Oscilloscope settings from [Oscilloscope::snapshot]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    OscilloscopeSnapshot {
+        sample_rate_hertz: f64,
+        buffer_size: usize,
+        acquisition_mode: AcquisitionMode,
+        trigger_source: TriggerSource,
+        trigger_type: TriggerType,
+        trigger_condition: SamplingSlope,
+        trigger_level_volts: f64,
+        trigger_hysteresis_volts: f64,
+        trigger_position_seconds: f64,
+        trigger_auto_timeout_seconds: f64,
+        trigger_holdoff_seconds: f64,
+        channels: Vec<ChannelSnapshot>
+    }
+}
+
+make_struct! {
+    /// Channel settings in an [OscilloscopeSnapshot]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    ChannelSnapshot {
+        index: u32,
+        enabled: bool,
+        range_volts: f64,
+        offset_volts: f64,
+        filter: Filter,
+        attenuation: f64
+    }
+}
+
+make_struct! {
+    /// Timestamp from [Oscilloscope::acquisition_time]: `seconds` since the Unix epoch
+    /// plus the fraction of a second `tick_num / tick_den`, where `tick_den` is the device's clock rate
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    AcquisitionTime {
+        seconds: u32,
+        tick_num: u32,
+        tick_den: u32
+    }
+}
+
+impl AcquisitionTime {
+    /// Time since the Unix epoch.
+    ///
+    /// An `f64` only has sub-microsecond precision this far from the epoch, so use [AcquisitionTime::since]
+    /// to compare timestamps at the clock's full resolution.
+    pub fn to_time(&self) -> Time {
+        Time::new::<second>(f64::from(self.seconds) + self.fraction())
+    }
+
+    /// Time from `earlier` to this timestamp, i.e. between captures on devices sharing a timebase
+    pub fn since(&self, earlier: &AcquisitionTime) -> Time {
+        Time::new::<second>(
+            (f64::from(self.seconds) - f64::from(earlier.seconds))
+                + (self.fraction() - earlier.fraction()),
+        )
+    }
+
+    pub fn to_system_time(&self) -> std::time::SystemTime {
+        std::time::UNIX_EPOCH
+            + Duration::from_secs(u64::from(self.seconds))
+            + Duration::from_secs_f64(self.fraction())
+    }
+
+    /// Fraction of a second, which is 0 if the device does not report one
+    fn fraction(&self) -> f64 {
+        if self.tick_den == 0 {
+            0.
+        } else {
+            f64::from(self.tick_num) / f64::from(self.tick_den)
+        }
+    }
+}
+
+make_struct! {
+    /// Samples that became available to read, were overwritten before they could be fetched,
+    /// or may have been overwritten while they were fetched, from [Oscilloscope::record_status]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    RecordStatus {
+        available: usize,
+        lost: usize,
+        corrupt: usize
+    }
+}
+
+make_struct! {
+    /// Snapshot of an acquisition's status from [Oscilloscope::acquisition_status].
+    ///
+    /// `trigger_time` is when the acquisition was triggered, with the device's timestamp resolution.
+    /// The `record_` counts are the samples that became available, were lost, or may be corrupt since the previous fetch,
+    /// and are only meaningful in [AcquisitionMode::Record].
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    AcquisitionStatus {
+        valid_samples: usize,
+        samples_left: usize,
+        write_index: usize,
+        auto_triggered: bool,
+        trigger_time: std::time::SystemTime,
+        record_available: usize,
+        record_lost: usize,
+        record_corrupt: usize
+    }
+}
+
+make_struct! {
+    /// Effective scaling of the oscilloscope's samples from [Oscilloscope::calibration_info]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    CalibrationInfo {
+        adc_bits: u32,
+        channels: Vec<ChannelCalibration>
+    }
+}
+
+make_struct! {
+    /// Effective scaling of a channel in a [CalibrationInfo]
+    ///
+    /// `volts_per_code` is the voltage of one ADC step, `range_volts / 2^adc_bits`.
+    /// Codes from [Channel::read_samples_raw] are left-aligned to 16 bits, so one step is `2^(16 - adc_bits)` of them.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    ChannelCalibration {
+        index: u32,
+        range_volts: f64,
+        offset_volts: f64,
+        attenuation: f64,
+        volts_per_code: f64
+    }
+}
+
+/// Ring buffer of the most recent samples of a channel in volts, from [Oscilloscope::ring_reader]
+#[derive(Debug)]
+pub struct RingReader<'handle> {
+    device_handle: c_int,
+    channel: c_int,
+    ring: VecDeque<f64>,
+    capacity: usize,
+    scratch: Vec<f64>,
+    dropped: u64,
+    phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> RingReader<'handle> {
+    /// Fetch data from the device and append the new samples, overwriting the oldest ones once full.
+    ///
+    /// Returns the number of new samples. Samples the device could not keep up with are counted in [RingReader::dropped].
+    pub fn poll(&mut self) -> Result<usize, WaveFormsError> {
+        get_int!(FDwfAnalogInStatus self.device_handle, 1)?;
+        let RecordStatus {
+            available,
+            lost,
+            corrupt,
+        } = record_status(self.device_handle)?;
+        self.dropped += (lost + corrupt) as u64;
+        if available == 0 {
+            return Ok(0);
+        }
+        ensure_enabled(self.device_handle, self.channel)?;
+        self.scratch.resize(available, 0.);
+        call!(FDwfAnalogInStatusData self.device_handle, self.channel, self.scratch.as_mut_ptr(), available as c_int)?;
+        // Only the newest samples fit if more arrived than the capacity
+        let skip = available.saturating_sub(self.capacity);
+        for sample in &self.scratch[skip..] {
+            if self.ring.len() == self.capacity {
+                self.ring.pop_front();
+            }
+            self.ring.push_back(*sample);
+        }
+        Ok(available)
+    }
+
+    /// The buffered samples, oldest first
+    pub fn slice(&mut self) -> &[f64] {
+        self.ring.make_contiguous()
+    }
+
+    /// Total number of samples lost or corrupted by the device since this reader was created
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Record mode reader that fills caller-provided buffers instead of allocating, from [Oscilloscope::double_buffer].
+///
+/// Each channel has a front and a back buffer. [DoubleBuffer::poll] reads new samples into the back buffers
+/// and swaps them to the front, where they can be read until the next poll.
+/// The borrow checker enforces this: the [DoubleBufferFill] returned by a poll must be dropped before polling again.
+/// The buffers are never resized, and can be taken back with [DoubleBuffer::into_buffers].
+#[derive(Debug)]
+pub struct DoubleBuffer<'handle> {
+    device_handle: c_int,
+    channels: Vec<c_int>,
+    front: Vec<Vec<f64>>,
+    back: Vec<Vec<f64>>,
+    phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> DoubleBuffer<'handle> {
+    /// Fetch data from the device and fill the buffers with the new samples.
+    ///
+    /// When more samples are available than fit in a buffer, only the newest are kept and the rest are counted in [DoubleBufferFill::overflowed].
+    pub fn poll(&mut self) -> Result<DoubleBufferFill<'_>, WaveFormsError> {
+        get_int!(FDwfAnalogInStatus self.device_handle, 1)?;
+        let RecordStatus {
+            available,
+            lost,
+            corrupt,
+        } = record_status(self.device_handle)?;
+        let capacity = self.back.first().map(Vec::len).unwrap_or(0);
+        let len = available.min(capacity);
+        let skip = available - len;
+        if len > 0 {
+            for (channel, back) in self.channels.iter().zip(self.back.iter_mut()) {
+                ensure_enabled(self.device_handle, *channel)?;
+                call!(FDwfAnalogInStatusData2 self.device_handle, *channel, back.as_mut_ptr(), skip as c_int, len as c_int)?;
+            }
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+        Ok(DoubleBufferFill {
+            buffers: &self.front,
+            len,
+            lost,
+            corrupt,
+            overflowed: skip,
+        })
+    }
+
+    /// Take back the `(channel, front, back)` buffers
+    pub fn into_buffers(self) -> Vec<(u32, Vec<f64>, Vec<f64>)> {
+        self.channels
+            .into_iter()
+            .zip(self.front.into_iter().zip(self.back))
+            .map(|(channel, (front, back))| (channel as u32, front, back))
+            .collect()
+    }
+}
+
+/// Samples read by a [DoubleBuffer::poll]
+#[derive(Debug)]
+pub struct DoubleBufferFill<'buffer> {
+    buffers: &'buffer [Vec<f64>],
+    len: usize,
+    /// Samples the device overwrote before they could be fetched
+    pub lost: usize,
+    /// Samples that may have been overwritten while they were fetched
+    pub corrupt: usize,
+    /// Fetched samples that didn't fit in the buffers
+    pub overflowed: usize,
+}
+
+impl<'buffer> DoubleBufferFill<'buffer> {
+    /// New samples in volts of the `n`th channel given to [Oscilloscope::double_buffer]
+    pub fn channel(&self, n: usize) -> &'buffer [f64] {
+        &self.buffers[n][..self.len]
+    }
+
+    /// Number of new samples per channel
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
 #[derive(Debug)]
 pub struct Steps<T>
 where
@@ -287,6 +1412,10 @@ enum_and_support_bitfield! {
     }
 }
 
+kebab_case_strings! {
+    TriggerType { Edge, Pulse, Transition, Window }
+}
+
 enum_and_support_bitfield! {
     TriggerCondition i32 {
         Edge => trigtypeEdge,
@@ -304,25 +1433,214 @@ enum_and_support_bitfield! {
     }
 }
 
-pub struct Samples {}
+/// Detailed acquisition lifecycle step from [Oscilloscope::lifecycle_position]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LifecyclePosition {
+    /// Not acquiring
+    Idle,
+    /// Filling the part of the buffer before the trigger, see [Oscilloscope::prefill_progress]
+    PreTriggerFilling {
+        progress: f64,
+    },
+    /// Waiting for the trigger
+    Armed,
+    /// Triggered, but no samples after the trigger have been taken yet
+    Triggered,
+    /// Taking samples after the trigger
+    Acquiring {
+        valid_samples: usize,
+    },
+    Done,
+}
+
+/// ADC resolutions for [Oscilloscope::set_resolution]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum AdcResolution {
+    Bits12,
+    Bits14,
+    Bits16,
+}
+
+impl AdcResolution {
+    pub fn bits(self) -> u32 {
+        match self {
+            AdcResolution::Bits12 => 12,
+            AdcResolution::Bits14 => 14,
+            AdcResolution::Bits16 => 16,
+        }
+    }
+}
+
+/// Trigger behavior for [Oscilloscope::set_trigger_mode]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScopeTriggerMode {
+    /// Capture on every trigger, or after `timeout` without one so that the display keeps updating
+    Auto { timeout: Time },
+    /// Capture on every trigger and wait indefinitely for it
+    Normal,
+    /// Capture once on the next trigger, then stop
+    Single,
+}
 
 pub struct Channel<'handle> {
     device_handle: c_int,
     index: c_int,
+    enabled_cache: Option<EnabledCache>,
     phantom: std::marker::PhantomData<&'handle ()>,
 }
 
 impl<'handle> Channel<'handle> {
     pub fn enable(&mut self) -> Result<(), WaveFormsError> {
-        set_true!(FDwfAnalogInChannelEnableSet self.device_handle, self.index)
+        set_true!(FDwfAnalogInChannelEnableSet self.device_handle, self.index)?;
+        self.cache_enabled(true);
+        Ok(())
     }
 
     pub fn disable(&mut self) -> Result<(), WaveFormsError> {
-        set_false!(FDwfAnalogInChannelEnableSet self.device_handle, self.index)
+        set_false!(FDwfAnalogInChannelEnableSet self.device_handle, self.index)?;
+        self.cache_enabled(false);
+        Ok(())
     }
 
+    /// Served from the cache without asking the device if the scope was made [Oscilloscope::with_state_cache]
     pub fn is_enabled(&self) -> Result<bool, WaveFormsError> {
-        get_bool!(FDwfAnalogInChannelEnableGet self.device_handle, self.index)
+        let cached = self
+            .enabled_cache
+            .as_ref()
+            .and_then(|cache| cache.lock().unwrap().get(&self.index).copied());
+        if let Some(enabled) = cached {
+            return Ok(enabled);
+        }
+        let enabled = get_bool!(FDwfAnalogInChannelEnableGet self.device_handle, self.index)?;
+        self.cache_enabled(enabled);
+        Ok(enabled)
+    }
+
+    fn cache_enabled(&self, enabled: bool) {
+        if let Some(cache) = &self.enabled_cache {
+            cache.lock().unwrap().insert(self.index, enabled);
+        }
+    }
+
+    /// Read the first `count` samples of the channel fetched with [Oscilloscope::fetch].
+    ///
+    /// Fails with [WaveFormsErrorCode::InvalidParameter] if `count` is larger than [Oscilloscope::get_sample_buffer_size]
+    /// or the channel is not enabled.
+    pub fn read_samples(&self, count: usize) -> Result<Vec<ElectricPotential>, WaveFormsError> {
+        let buffer_size = get_int!(FDwfAnalogInBufferSizeGet self.device_handle)?.max(0) as usize;
+        if count > buffer_size {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} samples requested but the buffer has {}",
+                    count, buffer_size
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        Ok(status_data(self.device_handle, self.index, count)?
+            .into_iter()
+            .map(ElectricPotential::new::<volt>)
+            .collect())
+    }
+
+    /// Read `count` samples of the channel starting at sample `start`, i.e. to read only the new part of a buffer.
+    ///
+    /// Fails with [WaveFormsErrorCode::InvalidParameter] if the range goes past the end of the buffer.
+    pub fn read_samples_range(
+        &self,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<ElectricPotential>, WaveFormsError> {
+        let buffer_size = get_int!(FDwfAnalogInBufferSizeGet self.device_handle)?.max(0) as usize;
+        if start.saturating_add(count) > buffer_size {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} samples from index {} go past the end of the {} sample buffer",
+                    count, start, buffer_size
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        ensure_enabled(self.device_handle, self.index)?;
+        let mut samples = vec![0.; count];
+        call!(FDwfAnalogInStatusData2 self.device_handle, self.index, samples.as_mut_ptr(), start as c_int, count as c_int)?;
+        Ok(samples
+            .into_iter()
+            .map(ElectricPotential::new::<volt>)
+            .collect())
+    }
+
+    /// Read `count` raw ADC codes of the channel starting at sample `start`, without converting them to volts.
+    ///
+    /// Codes are left-aligned to 16 bits whatever the [Oscilloscope::adc_bit_width], so the ADC code is `code / 2^(16 - bits)`
+    /// and codes are converted with the channel's range and offset:
+    ///
+    /// `volts = code / 2^15 * range / 2 - offset`
+    ///
+    /// which is what [Channel::codes_to_volts] does.
+    /// Fails with [WaveFormsErrorCode::InvalidParameter] if the range goes past the end of the buffer.
+    pub fn read_samples_raw(&self, start: usize, count: usize) -> Result<Vec<i16>, WaveFormsError> {
+        let buffer_size = get_int!(FDwfAnalogInBufferSizeGet self.device_handle)?.max(0) as usize;
+        if start.saturating_add(count) > buffer_size {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} samples from index {} go past the end of the {} sample buffer",
+                    count, start, buffer_size
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        ensure_enabled(self.device_handle, self.index)?;
+        let mut codes = vec![0; count];
+        call!(FDwfAnalogInStatusData16 self.device_handle, self.index, codes.as_mut_ptr(), start as c_int, count as c_int)?;
+        Ok(codes)
+    }
+
+    /// Read `count` `(min, max)` pairs of the channel's noise envelope starting at pair `start`.
+    ///
+    /// The envelope is only produced with [Filter::MinMax], so this fails with [WaveFormsErrorCode::NotSupported]
+    /// for other filters, and with [WaveFormsErrorCode::InvalidParameter] if the range goes past the end of
+    /// [Oscilloscope::get_noise_buffer_size].
+    pub fn read_noise(
+        &self,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<(ElectricPotential, ElectricPotential)>, WaveFormsError> {
+        let filter = self.get_filter()?;
+        if filter != Filter::MinMax {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "the noise envelope needs the MinMax filter, but the channel uses {:?}",
+                    filter
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        let noise_size = get_int!(FDwfAnalogInNoiseSizeGet self.device_handle)?.max(0) as usize;
+        if start.saturating_add(count) > noise_size {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} pairs from index {} go past the end of the {} pair noise buffer",
+                    count, start, noise_size
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        ensure_enabled(self.device_handle, self.index)?;
+        let mut min = vec![0.; count];
+        let mut max = vec![0.; count];
+        call!(FDwfAnalogInStatusNoise2 self.device_handle, self.index, min.as_mut_ptr(), max.as_mut_ptr(), start as c_int, count as c_int)?;
+        Ok(min
+            .into_iter()
+            .zip(max)
+            .map(|(min, max)| {
+                (
+                    ElectricPotential::new::<volt>(min),
+                    ElectricPotential::new::<volt>(max),
+                )
+            })
+            .collect())
     }
 
     enum_getter_and_setter! {
@@ -333,6 +1651,49 @@ impl<'handle> Channel<'handle> {
         get_int!(FDwfAnalogInChannelFilterInfo self.device_handle).map(SupportedFilters::from)
     }
 
+    /// Like [Channel::set_filter], but fails with [WaveFormsErrorCode::NotSupported] if the device does not support the filter
+    pub fn set_filter_checked(&mut self, filter: Filter) -> Result<(), WaveFormsError> {
+        let supported = self.filters()?.as_enum_variants();
+        if !supported.contains(&filter) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{:?} filter is not supported, choose from {:?}",
+                    filter, supported
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        self.set_filter(filter)
+    }
+
+    /// Recommend a [Filter] for reducing the ADC frequency to `target_rate`, for a given purpose.
+    ///
+    /// See [FilterPurpose] for what is recommended. If the recommended filter is not supported
+    /// or the target rate is the maximum sample frequency, [Filter::Decimate] is recommended instead.
+    pub fn recommend_filter(
+        &self,
+        target_rate: Frequency,
+        purpose: FilterPurpose,
+    ) -> Result<Filter, WaveFormsError> {
+        let mut min = 0.;
+        let mut max = 0.;
+        call!(FDwfAnalogInFrequencyInfo self.device_handle, &mut min, &mut max)?;
+        if target_rate.get::<hertz>() >= max {
+            // Every conversion is stored, so filters make no difference
+            return Ok(Filter::Decimate);
+        }
+        let recommended = match purpose {
+            FilterPurpose::Timing => Filter::Decimate,
+            FilterPurpose::NoiseReduction => Filter::Average,
+            FilterPurpose::GlitchCapture => Filter::MinMax,
+        };
+        if self.filters()?.as_enum_variants().contains(&recommended) {
+            Ok(recommended)
+        } else {
+            Ok(Filter::Decimate)
+        }
+    }
+
     /// Voltage range steps supported by the scope
     /// Scope will have voltage axis limits of `(+/- range / 2) - offset`
     pub fn range_steps(&self) -> Result<Steps<ElectricPotential>, WaveFormsError> {
@@ -363,6 +1724,44 @@ impl<'handle> Channel<'handle> {
         range ElectricPotential<volt> FDwfAnalogInChannelRange device_handle, index
     }
 
+    /// Limit the channel's analog bandwidth to cut out-of-band noise, or `None` for full bandwidth.
+    ///
+    /// The SDK does not list the discrete limits a device has, it rounds the requested limit to one of them,
+    /// so read back the applied limit with [Channel::get_bandwidth_limit].
+    /// Fails with [WaveFormsErrorCode::InvalidParameter] if the limit is not positive,
+    /// and with [WaveFormsErrorCode::NotSupported] on devices without a bandwidth limiter, like the original Analog Discovery.
+    ///
+    /// Needs the `bandwidth_limit` feature and a WaveForms SDK that declares `FDwfAnalogInChannelBandwidthSet`.
+    #[cfg(feature = "bandwidth_limit")]
+    pub fn set_bandwidth_limit(&mut self, limit: Option<Frequency>) -> Result<(), WaveFormsError> {
+        let hz = limit.map(|limit| limit.get::<hertz>()).unwrap_or(0.);
+        if limit.is_some() && (hz.is_nan() || hz <= 0.) {
+            return Err(WaveFormsError {
+                reason: format!("bandwidth limit {} Hz is not positive", hz),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        call!(FDwfAnalogInChannelBandwidthSet self.device_handle, self.index, hz)?;
+        if limit.is_some() && self.get_bandwidth_limit()?.is_none() {
+            return Err(WaveFormsError {
+                reason: "device does not have a bandwidth limiter".to_owned(),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        Ok(())
+    }
+
+    /// The channel's bandwidth limit, or `None` for full bandwidth
+    #[cfg(feature = "bandwidth_limit")]
+    pub fn get_bandwidth_limit(&self) -> Result<Option<Frequency>, WaveFormsError> {
+        let hz = get_float!(FDwfAnalogInChannelBandwidthGet self.device_handle, self.index)?;
+        Ok(if hz > 0. {
+            Some(Frequency::new::<hertz>(hz))
+        } else {
+            None
+        })
+    }
+
     /// Voltage offset steps supported by the scope
     pub fn offset_steps(&self) -> Result<Steps<ElectricPotential>, WaveFormsError> {
         let mut min = 0.;
@@ -388,6 +1787,70 @@ impl<'handle> Channel<'handle> {
     pub fn get_attenuation(&self) -> Result<f64, WaveFormsError> {
         get_float!(FDwfAnalogInChannelAttenuationGet self.device_handle, self.index)
     }
+
+    /// The range at the probe tip, i.e. a 5 V range with a 10x probe is a 50 V effective range
+    pub fn effective_range(&self) -> Result<ElectricPotential, WaveFormsError> {
+        Ok(self.get_range()? * self.get_attenuation()?)
+    }
+
+    /// Convert a raw code from [Channel::read_samples_raw] to volts using the channel's current range and offset.
+    ///
+    /// Raw codes are left-aligned to 16 bits, so the ADC bit width doesn't change the scaling:
+    ///
+    /// `volts = code / 2^15 * range / 2 - offset`
+    pub fn code_to_volts(&self, code: i16) -> Result<ElectricPotential, WaveFormsError> {
+        Ok(self.codes_to_volts(&[code])?[0])
+    }
+
+    /// Fraction of `samples` from this channel that are at the limits of its range, where the ADC saturates.
+    ///
+    /// The limits follow from the channel's current range and offset like in [Channel::code_to_volts],
+    /// and samples within one ADC step of them count as clipped.
+    pub fn clipping_fraction(&self, samples: &[f64]) -> Result<f64, WaveFormsError> {
+        let bits = get_int!(FDwfAnalogInBitsInfo self.device_handle)?;
+        let range = self.get_range()?.get::<volt>();
+        let offset = self.get_offset()?.get::<volt>();
+        let lsb = range / 2f64.powi(bits);
+        Ok(clip_fraction(
+            samples,
+            -range / 2. - offset,
+            range / 2. - offset,
+            lsb,
+        ))
+    }
+
+    /// Whether any of `samples` are clipped, see [Channel::clipping_fraction]. If so, widen the range.
+    pub fn is_clipping(&self, samples: &[f64]) -> Result<bool, WaveFormsError> {
+        self.clipping_fraction(samples)
+            .map(|fraction| fraction > 0.)
+    }
+
+    /// Like [Channel::code_to_volts], but only reads the channel configuration once
+    pub fn codes_to_volts(&self, codes: &[i16]) -> Result<Vec<ElectricPotential>, WaveFormsError> {
+        let range = self.get_range()?.get::<volt>();
+        let offset = self.get_offset()?.get::<volt>();
+        Ok(codes
+            .iter()
+            .map(|code| ElectricPotential::new::<volt>(code_to_volts(*code, range, offset)))
+            .collect())
+    }
+}
+
+/// Fraction of `samples` within `lsb` of or beyond the `low` and `high` limits, see [Channel::clipping_fraction]
+pub(crate) fn clip_fraction(samples: &[f64], low: f64, high: f64, lsb: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.;
+    }
+    let clipped = samples
+        .iter()
+        .filter(|&&sample| sample <= low + lsb || sample >= high - lsb)
+        .count();
+    clipped as f64 / samples.len() as f64
+}
+
+/// See [Channel::code_to_volts]
+pub(crate) fn code_to_volts(code: i16, range: f64, offset: f64) -> f64 {
+    f64::from(code) / 2f64.powi(15) * range / 2. - offset
 }
 
 enum_and_support_bitfield! {
@@ -404,6 +1867,27 @@ enum_and_support_bitfield! {
     }
 }
 
+kebab_case_strings! {
+    SamplingSlope { Rise, Fall, Either }
+}
+
+/// What a [Filter] is being chosen for. See [Channel::recommend_filter].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FilterPurpose {
+    /// Samples are actual conversions, so signal timing is preserved.
+    ///
+    /// Recommends [Filter::Decimate]
+    Timing,
+    /// Averaging conversions filters out noise above the sample frequency and adds resolution.
+    ///
+    /// Recommends [Filter::Average]
+    NoiseReduction,
+    /// Short pulses between samples still show up in the minimum and maximum.
+    ///
+    /// Recommends [Filter::MinMax]
+    GlitchCapture,
+}
+
 enum_and_support_bitfield! {
     Filter i32 {
         /// Store every Nth ADC conversion, where N = ADC frequency /acquisition frequency.
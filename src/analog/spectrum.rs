@@ -0,0 +1,168 @@
+use std::f64::consts::PI;
+use uom::si::{f64::Frequency, frequency::hertz};
+
+/// Options for [spectrum] and [Oscilloscope::capture_spectrum](crate::analog::scope::Oscilloscope::capture_spectrum)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SpectrumConfig {
+    pub window: Window,
+    /// Number of frames whose power is averaged (RMS averaging), which lowers the noise floor's variance
+    pub averages: usize,
+    pub unit: SpectrumUnit,
+}
+
+impl Default for SpectrumConfig {
+    /// A single frame with a [Window::Hann] in [SpectrumUnit::Dbv]
+    fn default() -> Self {
+        Self {
+            window: Window::Hann,
+            averages: 1,
+            unit: SpectrumUnit::Dbv,
+        }
+    }
+}
+
+/// Window applied to each frame before the FFT
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum Window {
+    /// No window. Only accurate for signals that are periodic in the frame.
+    Rectangular,
+    /// Good frequency resolution, the usual choice
+    Hann,
+    Hamming,
+    /// Lower leakage than [Window::Hann] at the cost of wider peaks
+    Blackman,
+    /// Accurate amplitudes between bins, but the widest peaks
+    FlatTop,
+}
+
+impl Window {
+    /// Window coefficient of sample `n` of `len`
+    fn coefficient(self, n: usize, len: usize) -> f64 {
+        if len < 2 {
+            return 1.;
+        }
+        let x = 2. * PI * n as f64 / (len - 1) as f64;
+        match self {
+            Window::Rectangular => 1.,
+            Window::Hann => 0.5 - 0.5 * x.cos(),
+            Window::Hamming => 0.54 - 0.46 * x.cos(),
+            Window::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2. * x).cos(),
+            Window::FlatTop => {
+                0.215_578_95 - 0.416_631_58 * x.cos() + 0.277_263_158 * (2. * x).cos()
+                    - 0.083_578_947 * (3. * x).cos()
+                    + 0.006_947_368 * (4. * x).cos()
+            }
+        }
+    }
+}
+
+/// Units of [Spectrum::magnitudes]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum SpectrumUnit {
+    /// RMS volts
+    Linear,
+    /// Decibels relative to 1 V RMS
+    Dbv,
+    /// Decibels relative to 1 mW into 50 Ω
+    Dbm,
+}
+
+make_struct! {
+    /// Single-sided amplitude spectrum from [spectrum]. Magnitude `i` is at `i * bin_width`.
+    Spectrum {
+        bin_width: Frequency,
+        magnitudes: Vec<f64>
+    }
+}
+
+/// Amplitude spectrum of equally long `frames` of samples in volts, taken at `sample_rate`.
+///
+/// Each frame is windowed and zero padded to a power of two, and the power of all frames is averaged.
+/// Magnitudes are corrected for the window's gain, so a sine wave's peak reads its RMS voltage.
+/// Returns [None] if there are no frames or they are empty or of different lengths.
+pub fn spectrum(
+    frames: &[Vec<f64>],
+    sample_rate: Frequency,
+    config: &SpectrumConfig,
+) -> Option<Spectrum> {
+    let len = frames.first()?.len();
+    if len == 0 || frames.iter().any(|frame| frame.len() != len) {
+        return None;
+    }
+    let window = (0..len)
+        .map(|n| config.window.coefficient(n, len))
+        .collect::<Vec<_>>();
+    let gain = window.iter().sum::<f64>();
+    let fft_len = len.next_power_of_two();
+    let mut power = vec![0.; fft_len / 2 + 1];
+    for frame in frames {
+        let mut bins = frame
+            .iter()
+            .zip(&window)
+            .map(|(sample, coefficient)| (sample * coefficient, 0.))
+            .chain(std::iter::repeat((0., 0.)))
+            .take(fft_len)
+            .collect::<Vec<_>>();
+        fft(&mut bins);
+        for (i, sum) in power.iter_mut().enumerate() {
+            let (re, im) = bins[i];
+            let peak = (re * re + im * im).sqrt() / gain;
+            // All but DC and Nyquist fold in their negative frequency
+            let rms = if i == 0 || i == fft_len / 2 {
+                peak
+            } else {
+                peak * 2. / 2f64.sqrt()
+            };
+            *sum += rms * rms;
+        }
+    }
+    let magnitudes = power
+        .into_iter()
+        .map(|sum| {
+            let rms = (sum / frames.len() as f64).sqrt();
+            match config.unit {
+                SpectrumUnit::Linear => rms,
+                SpectrumUnit::Dbv => 20. * rms.log10(),
+                SpectrumUnit::Dbm => 10. * (rms * rms / 50. / 1e-3).log10(),
+            }
+        })
+        .collect();
+    Some(Spectrum {
+        bin_width: Frequency::new::<hertz>(sample_rate.get::<hertz>() / fft_len as f64),
+        magnitudes,
+    })
+}
+
+/// In-place iterative radix-2 FFT of `(re, im)` pairs. The length must be a power of two.
+fn fft(data: &mut [(f64, f64)]) {
+    let len = data.len();
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= len {
+        let angle = -2. * PI / size as f64;
+        for start in (0..len).step_by(size) {
+            for k in 0..size / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (re, im) = data[start + k + size / 2];
+                let twiddled = (re * cos - im * sin, re * sin + im * cos);
+                let (even_re, even_im) = data[start + k];
+                data[start + k] = (even_re + twiddled.0, even_im + twiddled.1);
+                data[start + k + size / 2] = (even_re - twiddled.0, even_im - twiddled.1);
+            }
+        }
+        size *= 2;
+    }
+}
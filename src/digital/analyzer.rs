@@ -1,5 +1,8 @@
+use crate::analog::scope::RecordStatus;
 use crate::*;
+use std::io::Write;
 use std::os::raw::c_int;
+use std::time::Duration;
 use uom::si::f64::Frequency;
 use uom::si::frequency::hertz;
 
@@ -27,6 +30,147 @@ impl<'handle> LogicAnalyzer<'handle> {
         get_int!(FDwfDigitalInStatus self.device_handle, 0).and_then(InstrumentState::try_from)
     }
 
+    /// Shorthand for [InstrumentState::is_running] on the current state
+    pub fn is_running(&self) -> Result<bool, WaveFormsError> {
+        self.state().map(|state| state.is_running())
+    }
+
+    /// Fetch data from the device and check the instrument state
+    pub fn fetch(&mut self) -> Result<InstrumentState, WaveFormsError> {
+        use core::convert::TryFrom;
+        get_int!(FDwfDigitalInStatus self.device_handle, 1).and_then(InstrumentState::try_from)
+    }
+
+    /// Keep fetching data from the device until the acquisition is [InstrumentState::Done]
+    pub fn wait_until_done(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.wait_until_done_or_cancel(timeout, None)
+    }
+
+    /// Like [LogicAnalyzer::wait_until_done], but another thread can cancel the wait with `token`.
+    ///
+    /// When cancelled, the acquisition is stopped and this fails with [WaveFormsErrorCode::Interrupted].
+    pub fn wait_until_done_cancellable(
+        &mut self,
+        timeout: Duration,
+        token: &CancelToken,
+    ) -> Result<(), WaveFormsError> {
+        self.wait_until_done_or_cancel(timeout, Some(token))
+    }
+
+    fn wait_until_done_or_cancel(
+        &mut self,
+        timeout: Duration,
+        token: Option<&CancelToken>,
+    ) -> Result<(), WaveFormsError> {
+        poll_until(
+            self,
+            |analyzer| Ok(analyzer.fetch()? == InstrumentState::Done),
+            LogicAnalyzer::stop,
+            timeout,
+            token,
+            "acquisition was not done",
+        )
+    }
+
+    /// Start a single acquisition and wait until it is [InstrumentState::Done]
+    pub fn capture_single(&mut self, timeout: Duration) -> Result<(), WaveFormsError> {
+        self.set_acquisition_mode(AcquisitionMode::Single)?;
+        self.start()?;
+        self.wait_until_done(timeout)
+    }
+
+    /// Capture a single buffer and write it to `writer` as a Value Change Dump for waveform viewers like GTKWave.
+    ///
+    /// Pin `n` is written as a signal named `channel_names[n]`, so only the first `channel_names.len()` pins are included.
+    /// Whitespace in names is replaced with underscores.
+    /// The timescale is derived from the sample rate, assuming the [ClockSource::Internal] clock,
+    /// and only transitions are written after the initial values.
+    /// Fails with [WaveFormsErrorCode::NotSupported] if [LogicAnalyzer::store_transitions] is enabled.
+    pub fn export_vcd<W: Write>(
+        &mut self,
+        mut writer: W,
+        channel_names: &[&str],
+        timeout: Duration,
+    ) -> Result<(), WaveFormsError> {
+        let bit_width = self.bit_width()? as usize;
+        if channel_names.len() > bit_width {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{} channel names were given, but there are only {} pins",
+                    channel_names.len(),
+                    bit_width
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(1),
+            });
+        }
+        let sample_period = f64::from(self.get_clock_divider()?.max(1))
+            / self.internal_clock_frequency()?.get::<hertz>();
+        self.capture_single(timeout)?;
+        let samples = self.read_samples()?;
+
+        let write_error = |err: std::io::Error| WaveFormsError {
+            reason: format!("failed to write VCD: {}", err),
+            error_code: WaveFormsErrorCode::Other,
+        };
+        let (magnitude, unit, unit_seconds) = vcd_timescale(sample_period);
+        let ticks_per_sample = (sample_period / unit_seconds).round() as u64;
+        writeln!(writer, "$timescale {} {} $end", magnitude, unit).map_err(write_error)?;
+        writeln!(writer, "$scope module logic $end").map_err(write_error)?;
+        for (pin, name) in channel_names.iter().enumerate() {
+            let name = name.split_whitespace().collect::<Vec<_>>().join("_");
+            writeln!(writer, "$var wire 1 {} {} $end", vcd_identifier(pin), name)
+                .map_err(write_error)?;
+        }
+        writeln!(writer, "$upscope $end").map_err(write_error)?;
+        writeln!(writer, "$enddefinitions $end").map_err(write_error)?;
+
+        let mut previous = None;
+        for (i, sample) in samples.iter().enumerate() {
+            let changed = match previous {
+                Some(previous) => previous ^ sample,
+                None => u32::MAX,
+            };
+            previous = Some(*sample);
+            if (0..channel_names.len()).all(|pin| changed & (1 << pin) == 0) {
+                continue;
+            }
+            writeln!(writer, "#{}", i as u64 * ticks_per_sample).map_err(write_error)?;
+            for pin in (0..channel_names.len()).filter(|pin| changed & (1 << pin) != 0) {
+                writeln!(writer, "{}{}", (sample >> pin) & 1, vcd_identifier(pin))
+                    .map_err(write_error)?;
+            }
+        }
+        writeln!(writer, "#{}", samples.len() as u64 * ticks_per_sample).map_err(write_error)?;
+        writer.flush().map_err(write_error)
+    }
+
+    /// Read the fetched buffer with one value per sample, bit `n` being pin `n`.
+    ///
+    /// Fails with [WaveFormsErrorCode::NotSupported] when only transitions are stored,
+    /// since the samples would not be evenly spaced.
+    fn read_samples(&mut self) -> Result<Vec<u32>, WaveFormsError> {
+        if self.store_transitions()? {
+            return Err(WaveFormsError {
+                reason: "only transitions are stored, so samples are not evenly spaced".to_owned(),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        let bytes_per_sample =
+            (get_int!(FDwfDigitalInSampleFormatGet self.device_handle)? / 8).clamp(1, 4) as usize;
+        let count = self.get_buffer_size()?;
+        let mut data = vec![0u8; count * bytes_per_sample];
+        call!(FDwfDigitalInStatusData self.device_handle, data.as_mut_ptr() as *mut std::os::raw::c_void, data.len() as c_int)?;
+        Ok(data
+            .chunks_exact(bytes_per_sample)
+            .map(|bytes| {
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0, |value, byte| value << 8 | u32::from(*byte))
+            })
+            .collect())
+    }
+
     /// On-device clock source frequency
     pub fn internal_clock_frequency(&self) -> Result<Frequency, WaveFormsError> {
         get_float!(FDwfDigitalInInternalClockInfo self.device_handle)
@@ -45,6 +189,28 @@ impl<'handle> LogicAnalyzer<'handle> {
         Ok(get_int!(FDwfDigitalInDividerInfo self.device_handle)?)
     }
 
+    /// Set the clock divider for the closest sample rate to `rate` from the internal clock, returning the rate that was applied
+    pub fn set_sample_rate(&mut self, rate: Frequency) -> Result<Frequency, WaveFormsError> {
+        let clock = self.internal_clock_frequency()?.get::<hertz>();
+        let max_divider = self.max_clock_divider()?.max(1);
+        let divider = (clock / rate.get::<hertz>()).round();
+        if !(1. ..=f64::from(max_divider)).contains(&divider) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "sample rate {} Hz is outside of {} Hz to {} Hz",
+                    rate.get::<hertz>(),
+                    clock / f64::from(max_divider),
+                    clock
+                ),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        self.set_clock_divider(divider as u32)?;
+        Ok(Frequency::new::<hertz>(
+            clock / f64::from(self.get_clock_divider()?.max(1)),
+        ))
+    }
+
     pub fn bit_width(&self) -> Result<u32, WaveFormsError> {
         use std::convert::TryFrom;
         get_int!(FDwfDigitalInBitsInfo self.device_handle).map(|x| u32::try_from(x).unwrap_or(0))
@@ -82,6 +248,279 @@ impl<'handle> LogicAnalyzer<'handle> {
         get_int!(FDwfDigitalInAcquisitionModeInfo self.device_handle)
             .map(SupportedAcquisitionModes::from)
     }
+
+    /// Like [LogicAnalyzer::set_acquisition_mode], but fails with [WaveFormsErrorCode::NotSupported] if the device does not support the mode
+    pub fn set_acquisition_mode_checked(
+        &mut self,
+        mode: AcquisitionMode,
+    ) -> Result<(), WaveFormsError> {
+        let supported = self.acquisition_modes()?.as_enum_variants();
+        if !supported.contains(&mode) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "{:?} acquisition mode is not supported, choose from {:?}",
+                    mode, supported
+                ),
+                error_code: WaveFormsErrorCode::NotSupported,
+            });
+        }
+        self.set_acquisition_mode(mode)
+    }
+
+    enum_getter_and_setter! {
+        /// Use [TriggerSource::DetectorDigitalIn] to trigger on the pin conditions of [LogicAnalyzer::set_trigger]
+        trigger_source TriggerSource FDwfDigitalInTriggerSource device_handle
+    }
+
+    /// Trigger sources accepted by [LogicAnalyzer::set_trigger_source], which may be a subset of [DeviceHandle::trigger_sources]
+    pub fn supported_trigger_sources(&self) -> Result<SupportedTriggerSources, WaveFormsError> {
+        get_int!(FDwfDigitalInTriggerSourceInfo self.device_handle)
+            .map(SupportedTriggerSources::from)
+    }
+
+    /// Pins that support each trigger condition as `(low, high, rising, falling)` bit masks
+    pub fn trigger_pins(&self) -> Result<(u32, u32, u32, u32), WaveFormsError> {
+        let mut low = 0;
+        let mut high = 0;
+        let mut rising = 0;
+        let mut falling = 0;
+        call!(FDwfDigitalInTriggerInfo self.device_handle, &mut low, &mut high, &mut rising, &mut falling)?;
+        Ok((low, high, rising, falling))
+    }
+
+    /// Set the trigger condition of the digital in detector.
+    ///
+    /// Each argument is a bit mask of pins. The trigger occurs when all of the level conditions
+    /// (`low` and `high`) are met and any of the edge conditions (`rising` and `falling`) occur.
+    pub fn set_trigger(
+        &mut self,
+        low: u32,
+        high: u32,
+        rising: u32,
+        falling: u32,
+    ) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalInTriggerSet self.device_handle, low, high, rising, falling)
+    }
+
+    /// Set the trigger condition of the digital in detector pin by pin. See [pin_trigger_masks].
+    pub fn set_pin_triggers(
+        &mut self,
+        conditions: &[(u8, PinTrigger)],
+    ) -> Result<(), WaveFormsError> {
+        let (low, high, rising, falling) = pin_trigger_masks(conditions)?;
+        self.set_trigger(low, high, rising, falling)
+    }
+
+    /// Trigger when the pins selected by `mask` equal `value`, i.e. `trigger_on_value(0xff, 0x3f)`
+    /// to trigger when an 8 bit bus on pins 0 to 7 reads `0x3f`. See [value_trigger_masks].
+    ///
+    /// Like [LogicAnalyzer::set_trigger], the trigger source must be [TriggerSource::DetectorDigitalIn].
+    pub fn trigger_on_value(&mut self, mask: u32, value: u32) -> Result<(), WaveFormsError> {
+        let (low, high) = value_trigger_masks(mask, value)?;
+        self.set_trigger(low, high, 0, 0)
+    }
+
+    /// Trigger condition of the digital in detector as `(low, high, rising, falling)` bit masks
+    pub fn get_trigger(&self) -> Result<(u32, u32, u32, u32), WaveFormsError> {
+        let mut low = 0;
+        let mut high = 0;
+        let mut rising = 0;
+        let mut falling = 0;
+        call!(FDwfDigitalInTriggerGet self.device_handle, &mut low, &mut high, &mut rising, &mut falling)?;
+        Ok((low, high, rising, falling))
+    }
+
+    /// Set the condition that resets the digital in detector, with bit masks of pins like [LogicAnalyzer::set_trigger].
+    ///
+    /// When the reset condition occurs, the detector returns to its initial state and any progress
+    /// towards the trigger condition is discarded. This allows triggering on a specific sequence,
+    /// i.e. arming on one condition and starting over if another occurs first.
+    pub fn set_trigger_reset(
+        &mut self,
+        low: u32,
+        high: u32,
+        rising: u32,
+        falling: u32,
+    ) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalInTriggerResetSet self.device_handle, low, high, rising, falling)
+    }
+
+    /// Only trigger once the [LogicAnalyzer::set_trigger] condition has occurred `count` times,
+    /// i.e. on the 5th rising clock edge after chip select goes low.
+    ///
+    /// When `restart` is set, the counter starts over for every acquisition.
+    /// The [LogicAnalyzer::set_trigger_reset] condition also resets the counter.
+    pub fn set_trigger_count(&mut self, count: u32, restart: bool) -> Result<(), WaveFormsError> {
+        use std::convert::TryFrom;
+        let count = c_int::try_from(count).map_err(|_| WaveFormsError {
+            reason: format!("trigger count {} is too large", count),
+            error_code: WaveFormsErrorCode::InvalidParameter(0),
+        })?;
+        call!(FDwfDigitalInTriggerCountSet self.device_handle, count, restart as c_int)
+    }
+
+    /// Only store samples when a pin changes, which extends how long a record can be for sparse signals.
+    ///
+    /// This applies to [AcquisitionMode::Record]. Every pin is watched for changes when enabled.
+    /// The device then stores one sample per transition rather than one per clock,
+    /// so the stored samples are not evenly spaced in time and [LogicAnalyzer::export_vcd] refuses them.
+    /// The counts from [LogicAnalyzer::record_status] are then stored transitions rather than clock samples,
+    /// so a mostly idle bus fills the buffer far slower than the sample rate suggests.
+    /// Use [expand_transitions] to rebuild the full sample stream from `(sample index, value)` pairs.
+    pub fn set_store_transitions(&mut self, enable: bool) -> Result<(), WaveFormsError> {
+        let mask = if enable {
+            u32::MAX >> (32 - self.bit_width()?.clamp(1, 32))
+        } else {
+            0
+        };
+        call!(FDwfDigitalInSampleSensibleSet self.device_handle, mask)
+    }
+
+    /// Whether only transitions are stored, see [LogicAnalyzer::set_store_transitions]
+    pub fn store_transitions(&self) -> Result<bool, WaveFormsError> {
+        Ok(get_int!(FDwfDigitalInSampleSensibleGet self.device_handle)? != 0)
+    }
+
+    /// Samples that became available, were lost, or may be corrupt since the previous fetch in [AcquisitionMode::Record].
+    ///
+    /// When [LogicAnalyzer::store_transitions] is enabled these count stored transitions.
+    pub fn record_status(&self) -> Result<RecordStatus, WaveFormsError> {
+        let mut available = 0;
+        let mut lost = 0;
+        let mut corrupt = 0;
+        call!(FDwfDigitalInStatusRecord self.device_handle, &mut available, &mut lost, &mut corrupt)?;
+        Ok(RecordStatus {
+            available: available.max(0) as usize,
+            lost: lost.max(0) as usize,
+            corrupt: corrupt.max(0) as usize,
+        })
+    }
+}
+
+/// Rebuild `sample_count` evenly spaced samples from `(sample index, value)` transition pairs sorted by index,
+/// i.e. the transitions stored with [LogicAnalyzer::set_store_transitions] and the sample index of each.
+///
+/// Each value is held until the next transition. Samples before the first transition take its value,
+/// transitions at or past `sample_count` are ignored, and an empty slice of transitions yields all zeroes.
+/// Transitions that were [RecordStatus::lost] cannot be recovered, so the value before them is held too long.
+pub fn expand_transitions(transitions: &[(u64, u32)], sample_count: usize) -> Vec<u32> {
+    let mut samples = Vec::with_capacity(sample_count);
+    let mut value = transitions.first().map(|(_, value)| *value).unwrap_or(0);
+    let mut next = transitions.iter().peekable();
+    for i in 0..sample_count as u64 {
+        while let Some((_, next_value)) = next.next_if(|(index, _)| *index <= i) {
+            value = *next_value;
+        }
+        samples.push(value);
+    }
+    samples
+}
+
+/// The coarsest VCD timescale that evenly divides `period` seconds, as `(magnitude, unit, seconds)`
+fn vcd_timescale(period: f64) -> (u32, &'static str, f64) {
+    let units = [
+        ("s", 1.),
+        ("ms", 1e-3),
+        ("us", 1e-6),
+        ("ns", 1e-9),
+        ("ps", 1e-12),
+    ];
+    for (unit, unit_seconds) in units.iter() {
+        for magnitude in [100, 10, 1].iter() {
+            let seconds = unit_seconds * f64::from(*magnitude);
+            let ticks = period / seconds;
+            if ticks >= 1. && (ticks - ticks.round()).abs() < 1e-6 {
+                return (*magnitude, unit, seconds);
+            }
+        }
+    }
+    (1, "fs", 1e-15)
+}
+
+/// Short identifier code for a VCD signal, made of printable ASCII characters
+fn vcd_identifier(mut index: usize) -> String {
+    let mut identifier = String::new();
+    loop {
+        identifier.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return identifier;
+        }
+        index -= 1;
+    }
+}
+
+/// Send the logic analyzer's settings to the device, restarting the acquisition if it is running so that it keeps running
+pub(crate) fn apply_settings(device_handle: c_int) -> Result<(), WaveFormsError> {
+    use core::convert::TryFrom;
+    let running = get_int!(FDwfDigitalInStatus device_handle, 0)
+        .and_then(InstrumentState::try_from)?
+        .is_running();
+    call!(FDwfDigitalInConfigure device_handle, 1, running as c_int)
+}
+
+/// Condition on a single pin for [LogicAnalyzer::set_pin_triggers]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PinTrigger {
+    Low,
+    High,
+    Rising,
+    Falling,
+}
+
+/// Build the `(low, high, rising, falling)` bit masks for [LogicAnalyzer::set_trigger] from per-pin conditions.
+///
+/// All of the level conditions must be met while any of the edge conditions occurs.
+/// A pin can have both [PinTrigger::Rising] and [PinTrigger::Falling] to trigger on either edge,
+/// but any other combination of conditions on the same pin can never be met and is an error.
+pub fn pin_trigger_masks(
+    conditions: &[(u8, PinTrigger)],
+) -> Result<(u32, u32, u32, u32), WaveFormsError> {
+    let mut masks = [0u32; 4];
+    for (pin, trigger) in conditions {
+        if *pin >= 32 {
+            return Err(WaveFormsError {
+                reason: format!("pin {} is out of range, there are at most 32 pins", pin),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let bit = 1 << pin;
+        let (index, compatible) = match trigger {
+            PinTrigger::Low => (0, 0),
+            PinTrigger::High => (1, 1),
+            PinTrigger::Rising => (2, 3),
+            PinTrigger::Falling => (3, 2),
+        };
+        let conflict = masks
+            .iter()
+            .enumerate()
+            .any(|(i, mask)| i != index && i != compatible && mask & bit != 0);
+        if conflict {
+            return Err(WaveFormsError {
+                reason: format!("pin {} has conflicting trigger conditions", pin),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        masks[index] |= bit;
+    }
+    Ok((masks[0], masks[1], masks[2], masks[3]))
+}
+
+/// Build the `(low, high)` level masks for [LogicAnalyzer::set_trigger] so that it triggers when `(pins & mask) == value`.
+///
+/// Pins in `mask` are low where `value` has a 0 bit and high where it has a 1 bit, and other pins are ignored.
+/// Fails with [WaveFormsErrorCode::InvalidParameter] if `value` has bits outside of `mask`, since that can never match.
+pub fn value_trigger_masks(mask: u32, value: u32) -> Result<(u32, u32), WaveFormsError> {
+    if value & !mask != 0 {
+        return Err(WaveFormsError {
+            reason: format!(
+                "value {:#x} has bits outside of mask {:#x}, so it can never match",
+                value, mask
+            ),
+            error_code: WaveFormsErrorCode::InvalidParameter(1),
+        });
+    }
+    Ok((mask & !value, mask & value))
 }
 
 enum_and_support_bitfield! {
@@ -29,6 +29,22 @@ impl<'handle> PatternGenerator<'handle> {
         get_int!(FDwfDigitalOutStatus self.device_handle).and_then(InstrumentState::try_from)
     }
 
+    /// Shorthand for [InstrumentState::is_running] on the current state
+    pub fn is_running(&self) -> Result<bool, WaveFormsError> {
+        self.state().map(|state| state.is_running())
+    }
+
+    enum_getter_and_setter! {
+        /// The generator waits for this trigger after [PatternGenerator::start] when it is not [TriggerSource::None]
+        trigger_source TriggerSource FDwfDigitalOutTriggerSource device_handle
+    }
+
+    /// Trigger sources accepted by [PatternGenerator::set_trigger_source], which may be a subset of [DeviceHandle::trigger_sources]
+    pub fn supported_trigger_sources(&self) -> Result<SupportedTriggerSources, WaveFormsError> {
+        get_int!(FDwfDigitalOutTriggerSourceInfo self.device_handle)
+            .map(SupportedTriggerSources::from)
+    }
+
     pub fn run_time_max(&self) -> Result<Time, WaveFormsError> {
         let mut min = 0.;
         let mut max = 0.;
@@ -95,6 +111,9 @@ impl<'handle> PatternGenerator<'handle> {
     }
 
     /// Set the playback frequency. i.e. 32kHz, 44.1kHz, 48kHz
+    ///
+    /// The SDK neither reports the range of play rates nor reads back the rate it applied,
+    /// so the exact playback rate of a device cannot be queried.
     pub fn set_play_rate(&mut self, frequency: Frequency) -> Result<(), WaveFormsError> {
         call!(FDwfDigitalOutPlayRateSet self.device_handle, frequency.get::<hertz>())
     }
@@ -103,6 +122,9 @@ impl<'handle> PatternGenerator<'handle> {
     ///
     /// The sample count is equal to `data.len() * 8 / bitrate`.
     /// If the bitrate is 16, sample count should be even.
+    ///
+    /// Unlike the waveform generator, the SDK does not report free, lost, or corrupted samples for pattern playback,
+    /// so a streaming loop can only be paced by the [PatternGenerator::set_play_rate].
     pub fn set_play_data(&mut self, data: &[u8], bitrate: Bitrate) -> Result<(), WaveFormsError> {
         let sample_count = if bitrate == Bitrate::Sixteen {
             data.len() as c_uint / 2
@@ -210,6 +232,75 @@ impl<'handle> Channel<'handle> {
         Ok((min, max))
     }
 
+    /// Check every setting in `config` against the channel's limits, then apply them.
+    ///
+    /// Nothing is applied if any setting is out of range or unsupported.
+    /// Returns the configuration read back from the device, which may be quantized.
+    pub fn configure(
+        &mut self,
+        config: &DigitalOutChannelConfig,
+    ) -> Result<DigitalOutChannelConfig, WaveFormsError> {
+        let invalid = |reason: String| WaveFormsError {
+            reason,
+            error_code: WaveFormsErrorCode::InvalidParameter(0),
+        };
+        if let Some(ty) = config.ty {
+            let types = self.types()?.as_enum_variants();
+            if !types.contains(&ty) {
+                return Err(invalid(format!(
+                    "type {:?} is not supported, choose from {:?}",
+                    ty, types
+                )));
+            }
+        }
+        if let Some(divider) = config.divider {
+            let range = self.divider_range()?;
+            if !range.contains(&divider) {
+                return Err(invalid(format!(
+                    "divider {} is outside of {:?}",
+                    divider, range
+                )));
+            }
+        }
+        if let Some((low, high)) = config.counter {
+            let range = self.counter_range()?;
+            if !range.contains(&low) || !range.contains(&high) {
+                return Err(invalid(format!(
+                    "counter low {} or high {} is outside of {:?}",
+                    low, high, range
+                )));
+            }
+        }
+        if let Some(idle) = config.idle {
+            let idles = self.idles()?.as_enum_variants();
+            if !idles.contains(&idle) {
+                return Err(invalid(format!(
+                    "idle {:?} is not supported, choose from {:?}",
+                    idle, idles
+                )));
+            }
+        }
+
+        if let Some(ty) = config.ty {
+            self.set_type(ty)?;
+        }
+        if let Some(divider) = config.divider {
+            self.set_divider(divider)?;
+        }
+        if let Some((low, high)) = config.counter {
+            self.set_counter(low, high)?;
+        }
+        if let Some(idle) = config.idle {
+            self.set_idle(idle)?;
+        }
+        Ok(DigitalOutChannelConfig {
+            ty: Some(self.get_type()?),
+            divider: Some(self.get_divider()?),
+            counter: Some(self.get_counter()?),
+            idle: Some(self.get_idle()?),
+        })
+    }
+
     pub fn custom_data_max_length(&self) -> Result<usize, WaveFormsError> {
         use std::convert::TryFrom;
         get_int!(FDwfDigitalOutDataInfo self.device_handle, self.index)
@@ -222,6 +313,114 @@ impl<'handle> Channel<'handle> {
     pub fn set_custom_data(&mut self, bits: &[u8]) -> Result<(), WaveFormsError> {
         call!(FDwfDigitalOutDataSet self.device_handle, self.index, bits.as_ptr() as *mut c_void, bits.len() as c_uint)
     }
+
+    /// Switches the channel to [Mode::Tristate] and sets its custom data.
+    ///
+    /// Each sample is an output value and an output enable. When the output enable is false,
+    /// the pin is left in high impedance for that sample. See [pack_tristate_bits] for the layout.
+    pub fn set_tristate_data(
+        &mut self,
+        values: &[bool],
+        enables: &[bool],
+    ) -> Result<(), WaveFormsError> {
+        let bits = pack_tristate_bits(values, enables)?;
+        self.set_mode(Mode::Tristate)?;
+        call!(FDwfDigitalOutDataSet self.device_handle, self.index, bits.as_ptr() as *mut c_void, (values.len() * 2) as c_uint)
+    }
+
+    /// Switches the channel to [Type::ROM] and loads its lookup table.
+    ///
+    /// The channel outputs `table[address]`, high for `true`, where the address is formed by the input states of
+    /// the digital I/O pins, with DIO 0 as the least significant bit. A table of `2^n` entries
+    /// uses the first `n` pins as the address, so its length must be a power of two.
+    pub fn set_rom_table(&mut self, table: &[bool]) -> Result<(), WaveFormsError> {
+        if !table.len().is_power_of_two() {
+            return Err(WaveFormsError {
+                reason: format!("table length {} is not a power of two", table.len()),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let mut bits = vec![0u8; table.len().div_ceil(8)];
+        for (address, value) in table.iter().enumerate() {
+            bits[address / 8] |= (*value as u8) << (address % 8);
+        }
+        self.set_type(Type::ROM)?;
+        call!(FDwfDigitalOutDataSet self.device_handle, self.index, bits.as_ptr() as *mut c_void, table.len() as c_uint)
+    }
+}
+
+/// Pattern generator channel settings that are checked against the channel's limits and applied together.
+///
+/// Settings that are left unset are not changed. See [Channel::configure].
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DigitalOutChannelConfig {
+    pub ty: Option<Type>,
+    pub divider: Option<u32>,
+    /// `(low, high)` counter values
+    pub counter: Option<(u32, u32)>,
+    pub idle: Option<Idle>,
+}
+
+impl DigitalOutChannelConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ty(mut self, ty: Type) -> Self {
+        self.ty = Some(ty);
+        self
+    }
+
+    pub fn divider(mut self, divider: u32) -> Self {
+        self.divider = Some(divider);
+        self
+    }
+
+    pub fn counter(mut self, low: u32, high: u32) -> Self {
+        self.counter = Some((low, high));
+        self
+    }
+
+    pub fn idle(mut self, idle: Idle) -> Self {
+        self.idle = Some(idle);
+        self
+    }
+}
+
+/// Interleaves output values and output enables into the LSB first bit buffer used for [Mode::Tristate] custom data.
+///
+/// Sample `i` has its output value at bit `2 * i` and its output enable at bit `2 * i + 1`.
+pub fn pack_tristate_bits(values: &[bool], enables: &[bool]) -> Result<Vec<u8>, WaveFormsError> {
+    if values.len() != enables.len() {
+        return Err(WaveFormsError {
+            reason: format!(
+                "got {} output values but {} output enables",
+                values.len(),
+                enables.len()
+            ),
+            error_code: WaveFormsErrorCode::InvalidParameter(1),
+        });
+    }
+    let mut bits = vec![0u8; values.len().div_ceil(4)];
+    for (i, (value, enable)) in values.iter().zip(enables).enumerate() {
+        if *value {
+            bits[i / 4] |= 1 << ((i % 4) * 2);
+        }
+        if *enable {
+            bits[i / 4] |= 1 << ((i % 4) * 2 + 1);
+        }
+    }
+    Ok(bits)
+}
+
+/// Splits a [Mode::Tristate] bit buffer back into `(values, enables)`. See [pack_tristate_bits].
+pub fn unpack_tristate_bits(bits: &[u8], sample_count: usize) -> (Vec<bool>, Vec<bool>) {
+    (0..sample_count.min(bits.len() * 4))
+        .map(|i| {
+            let pair = bits[i / 4] >> ((i % 4) * 2);
+            (pair & 1 != 0, pair & 2 != 0)
+        })
+        .unzip()
 }
 
 enum_and_support_bitfield! {
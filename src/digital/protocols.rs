@@ -1,9 +1,228 @@
+use crate::*;
 use std::os::raw::c_int;
+use uom::si::{f64::Frequency, frequency::hertz};
 
+/// Serial protocol engines, which drive digital I/O pins.
+///
+/// The engines share pins, so only one can be active at a time: each engine borrows this mutably.
+/// Each engine is a guard that resets it when dropped, releasing its pins for the next one.
 #[derive(Debug)]
 pub struct Protocols<'handle> {
     pub(crate) device_handle: c_int,
     pub(crate) phantom: std::marker::PhantomData<&'handle ()>,
 }
 
-impl<'handle> Protocols<'handle> {}
+impl<'handle> Protocols<'handle> {
+    pub fn uart(&mut self) -> Uart<'_> {
+        Uart {
+            device_handle: self.device_handle,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn spi(&mut self) -> Spi<'_> {
+        Spi {
+            device_handle: self.device_handle,
+            three_wire: false,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Asynchronous serial using digital I/O pins
+///
+/// The engine is reset when this is dropped.
+#[derive(Debug)]
+pub struct Uart<'handle> {
+    device_handle: c_int,
+    phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> Uart<'handle> {
+    pub fn reset(&mut self) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartReset self.device_handle)
+    }
+
+    /// Baud rate
+    pub fn set_rate(&mut self, rate: Frequency) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartRateSet self.device_handle, rate.get::<hertz>())
+    }
+
+    /// Data bits per character
+    pub fn set_bits(&mut self, bits: u32) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartBitsSet self.device_handle, bits as c_int)
+    }
+
+    pub fn set_parity(&mut self, parity: Parity) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartParitySet self.device_handle, parity.into())
+    }
+
+    /// Stop bit length, in bits. i.e. 1, 1.5, 2
+    pub fn set_stop_bits(&mut self, stop_bits: f64) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartStopSet self.device_handle, stop_bits)
+    }
+
+    pub fn set_tx_pin(&mut self, pin: u32) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartTxSet self.device_handle, pin as c_int)
+    }
+
+    pub fn set_rx_pin(&mut self, pin: u32) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartRxSet self.device_handle, pin as c_int)
+    }
+
+    pub fn tx(&mut self, data: &[u8]) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalUartTx self.device_handle, data.as_ptr() as *mut c_char, data.len() as c_int)
+    }
+
+    /// Start receiving. Characters are buffered by the SDK until read with [Uart::rx].
+    pub fn start_rx(&mut self) -> Result<(), WaveFormsError> {
+        let mut count = 0;
+        let mut parity = 0;
+        // The SDK starts the receiver when it is given no buffer
+        call!(FDwfDigitalUartRx self.device_handle, std::ptr::null_mut(), 0, &mut count, &mut parity)
+    }
+
+    /// Read buffered characters into `buffer`, returning how many were read.
+    ///
+    /// Fails if the SDK buffer overflowed or a character had a parity error.
+    pub fn rx(&mut self, buffer: &mut [u8]) -> Result<usize, WaveFormsError> {
+        let (count, parity) = self.receive(buffer)?;
+        match parity {
+            0 => Ok(count),
+            parity if parity < 0 => Err(WaveFormsError {
+                reason: "receive buffer overflowed".to_owned(),
+                error_code: WaveFormsErrorCode::Other,
+            }),
+            parity => Err(WaveFormsError {
+                reason: format!("parity error at character {}", parity),
+                error_code: WaveFormsErrorCode::Other,
+            }),
+        }
+    }
+
+    /// Discard buffered characters, i.e. stale ones from before the receiver was configured.
+    ///
+    /// Returns how many were discarded. Overflows and parity errors are ignored.
+    pub fn flush_rx(&mut self) -> Result<usize, WaveFormsError> {
+        let mut buffer = [0; 256];
+        let mut discarded = 0;
+        loop {
+            match self.receive(&mut buffer)? {
+                (0, _) => return Ok(discarded),
+                (count, _) => discarded += count,
+            }
+        }
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<(usize, c_int), WaveFormsError> {
+        let mut count = 0;
+        let mut parity = 0;
+        call!(FDwfDigitalUartRx self.device_handle, buffer.as_mut_ptr() as *mut c_char, buffer.len() as c_int, &mut count, &mut parity)?;
+        Ok((count as usize, parity))
+    }
+}
+
+impl<'handle> Drop for Uart<'handle> {
+    fn drop(&mut self) {
+        let _ = self.reset();
+    }
+}
+
+/// SPI controller using digital I/O pins
+///
+/// Words are 8 bits. Chip select is controlled separately with [Spi::select].
+/// The engine is reset when this is dropped.
+#[derive(Debug)]
+pub struct Spi<'handle> {
+    device_handle: c_int,
+    three_wire: bool,
+    phantom: std::marker::PhantomData<&'handle ()>,
+}
+
+impl<'handle> Spi<'handle> {
+    pub fn reset(&mut self) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiReset self.device_handle)
+    }
+
+    /// Clock frequency
+    pub fn set_frequency(&mut self, frequency: Frequency) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiFrequencySet self.device_handle, frequency.get::<hertz>())
+    }
+
+    pub fn set_clock_pin(&mut self, pin: u32) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiClockSet self.device_handle, pin as c_int)
+    }
+
+    /// Assign a data line to a pin.
+    ///
+    /// Data line 0 is MOSI and data line 1 is MISO.
+    /// In three-wire mode, data line 0 is used in both directions and data line 1 is unused.
+    pub fn set_data_pin(&mut self, data_line: u32, pin: u32) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiDataSet self.device_handle, data_line as c_int, pin as c_int)
+    }
+
+    /// Clock polarity and phase as an SPI mode from 0 to 3
+    pub fn set_mode(&mut self, mode: u32) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiModeSet self.device_handle, mode as c_int)
+    }
+
+    pub fn set_msb_first(&mut self, msb_first: bool) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiOrderSet self.device_handle, msb_first as c_int)
+    }
+
+    /// Drive a chip select pin high or low
+    pub fn select(&mut self, pin: u32, high: bool) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiSelect self.device_handle, pin as c_int, high as c_int)
+    }
+
+    /// Share a single bidirectional data line (SIO) instead of separate MOSI and MISO lines.
+    ///
+    /// This is half duplex: see [Spi::write_read] for how transfers change.
+    pub fn set_three_wire(&mut self, enable: bool) {
+        self.three_wire = enable;
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiWrite self.device_handle, self.data_lines(), 8, data.as_ptr() as *mut c_uchar, data.len() as c_int)
+    }
+
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<(), WaveFormsError> {
+        call!(FDwfDigitalSpiRead self.device_handle, self.data_lines(), 8, buffer.as_mut_ptr(), buffer.len() as c_int)
+    }
+
+    /// Write `tx` while reading into `rx`.
+    ///
+    /// In three-wire mode the line can only go one way at a time, so all of `tx` is written
+    /// and then the line is turned around to read `rx`.
+    pub fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), WaveFormsError> {
+        if self.three_wire {
+            self.write(tx)?;
+            self.read(rx)
+        } else {
+            call!(FDwfDigitalSpiWriteRead self.device_handle, self.data_lines(), 8, tx.as_ptr() as *mut c_uchar, tx.len() as c_int, rx.as_mut_ptr(), rx.len() as c_int)
+        }
+    }
+
+    /// Data line mode for the SDK: 0 is a single bidirectional line, 1 is MOSI/MISO
+    fn data_lines(&self) -> c_int {
+        if self.three_wire {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+impl<'handle> Drop for Spi<'handle> {
+    fn drop(&mut self) {
+        let _ = self.reset();
+    }
+}
+
+enum_only! {
+    Parity c_int {
+        None => 0,
+        Odd => 1,
+        Even => 2
+    }
+}
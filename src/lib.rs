@@ -2,6 +2,8 @@ use paste::paste;
 use std::ffi::CStr;
 use std::ops::RangeInclusive;
 use std::os::raw::*;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 #[cfg(test)]
 mod tests;
@@ -16,8 +18,15 @@ mod bindings {
 }
 /// Digital input, output, and protocols
 pub mod digital;
+/// Device hot-plug detection
+pub mod watcher;
 
-use analog::{gen::WaveformGenerator, scope::Oscilloscope};
+use analog::{
+    gen::WaveformGenerator,
+    impedance::ImpedanceAnalyzer,
+    io::AnalogIo,
+    scope::{Oscilloscope, SupportedSamplingSlopes},
+};
 use bindings::*;
 use digital::{analyzer::LogicAnalyzer, gen::PatternGenerator, protocols::Protocols};
 
@@ -38,6 +47,23 @@ impl WaveFormsError {
     }
 }
 
+impl std::fmt::Display for WaveFormsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+impl std::error::Error for WaveFormsError {}
+
+/// Allows using `?` on SDK calls in functions that return [std::io::Result], i.e. when logging captures to a file.
+impl From<WaveFormsError> for std::io::Error {
+    // `std::io::Error::other` needs Rust 1.74
+    #[allow(clippy::io_other_error)]
+    fn from(err: WaveFormsError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::Other, err)
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum WaveFormsErrorCode {
@@ -51,7 +77,8 @@ pub enum WaveFormsErrorCode {
     NotSupported,
     /// N-th parameter in an SDK call is invalid
     ///
-    /// The Rust bindings are most likely to blame for this
+    /// The Rust bindings are most likely to blame for this.
+    /// Also used when the bindings reject the N-th argument of a method before calling the SDK.
     InvalidParameter(u8),
     /// Rust SDK bindings are not aware of this error code
     Other,
@@ -60,6 +87,81 @@ pub enum WaveFormsErrorCode {
     /// This can happen if the Rust SDK bindings are not up to date with the latest
     /// version of WaveForms SDK.
     UnknownVariant,
+    /// No attached device matched what was being looked for
+    NotFound,
+    /// An instrument did not reach the expected state in time
+    Timeout,
+    /// A wait was cancelled with its [CancelToken]
+    Interrupted,
+}
+
+/// Cancels a wait like [Oscilloscope::wait_until_done_cancellable] from another thread, i.e. a cancel button in a UI.
+///
+/// Clones share the same flag, so keep one clone for the waiting thread and give another to the canceling one.
+/// Once cancelled, a token stays cancelled until [CancelToken::reset].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make waits checking this token return [WaveFormsErrorCode::Interrupted]
+    pub fn cancel(&self) {
+        self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Clear the cancellation so the token can be used for another wait
+    pub fn reset(&self) {
+        self.0.store(false, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// How long to sleep between status checks when waiting on an instrument
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Poll an instrument until `done` returns true, sleeping [POLL_INTERVAL] between checks.
+///
+/// Fails with [WaveFormsErrorCode::Timeout] and `what` as the reason once `timeout` has passed,
+/// or calls `stop` and fails with [WaveFormsErrorCode::Interrupted] once `token` is cancelled.
+/// A timeout too large to be a deadline, like [Duration::MAX], waits indefinitely.
+pub(crate) fn poll_until<I, F, S>(
+    instrument: &mut I,
+    mut done: F,
+    stop: S,
+    timeout: Duration,
+    token: Option<&CancelToken>,
+    what: &str,
+) -> Result<(), WaveFormsError>
+where
+    F: FnMut(&mut I) -> Result<bool, WaveFormsError>,
+    S: FnOnce(&mut I) -> Result<(), WaveFormsError>,
+{
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+        if matches!(token, Some(token) if token.is_cancelled()) {
+            stop(instrument)?;
+            return Err(WaveFormsError {
+                reason: "wait was cancelled".to_owned(),
+                error_code: WaveFormsErrorCode::Interrupted,
+            });
+        }
+        if done(instrument)? {
+            return Ok(());
+        }
+        if matches!(deadline, Some(deadline) if Instant::now() >= deadline) {
+            return Err(WaveFormsError {
+                reason: format!("{} after {:?}", what, timeout),
+                error_code: WaveFormsErrorCode::Timeout,
+            });
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
 }
 
 impl WaveFormsError {
@@ -105,8 +207,36 @@ impl WaveFormsErrorCode {
 /// WaveForms SDK version (i.e. `3.16.3`)
 ///
 /// See [download page](https://reference.digilentinc.com/reference/software/waveforms/waveforms-3/start) for the latest version.
+///
+/// # Panics
+///
+/// If the SDK fails to report its version. Use [try_version] to handle that instead.
 pub fn version() -> String {
-    get_string!(FDwfGetVersion).unwrap()
+    try_version().unwrap()
+}
+
+/// Like [version], but returns an error if the SDK fails to report its version
+pub fn try_version() -> Result<String, WaveFormsError> {
+    get_string!(FDwfGetVersion)
+}
+
+/// Check that the WaveForms runtime is installed and working.
+///
+/// Call this at startup to report a helpful error rather than failing on the first SDK call.
+///
+/// The runtime library (libdwf) is dynamically linked, so if it is missing entirely, the program
+/// will fail to load before this can be called and the loader will report that libdwf could not be found.
+/// In either case, WaveForms must be installed from the [download page](https://reference.digilentinc.com/reference/software/waveforms/waveforms-3/start).
+pub fn init() -> Result<(), WaveFormsError> {
+    try_version()
+        .map(|_| ())
+        .map_err(|err| WaveFormsError {
+            reason: format!(
+                "WaveForms runtime is not working ({}), reinstall it from https://reference.digilentinc.com/reference/software/waveforms/waveforms-3/start",
+                err.reason
+            ),
+            error_code: err.error_code,
+        })
 }
 
 /// Discovered with [iter_devices]
@@ -121,23 +251,53 @@ pub struct Device {
 }
 
 impl Device {
+    pub fn device_type(&self) -> DeviceType {
+        self.ty
+    }
+
+    /// Name given to the device by the user
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn serial_number(&self) -> &str {
+        &self.serial_number
+    }
+
+    /// Configurations the device can be opened with, see [Device::open_with_config]
+    pub fn configs(&self) -> &[Config] {
+        &self.configs
+    }
+
     pub fn open_with_config(&self, config: &Config) -> Result<DeviceHandle, WaveFormsError> {
-        // TODO: libdwf doesn't actually return the correct error
-        // for this, overriding their logic here.
-        if get_bool!(FDwfEnumDeviceIsOpened self.index)? {
-            return Err(WaveFormsError {
-                reason: "device was already opened".to_owned(),
-                error_code: WaveFormsErrorCode::AlreadyOpened,
-            });
-        }
-        let handle = get_int!(FDwfDeviceConfigOpen self.index, config.index)?;
-        Ok(DeviceHandle {
-            handle: Some(handle),
-        })
+        let _enumeration = lock_enumeration();
+        self.open_enumerated(Some(config))
     }
 
     /// Acquires an exclusive lock on the device
     pub fn open(&self) -> Result<DeviceHandle, WaveFormsError> {
+        let _enumeration = lock_enumeration();
+        self.open_enumerated(None)
+    }
+
+    /// Open the device with the [ENUMERATION] lock held.
+    ///
+    /// Fails with [WaveFormsErrorCode::NotFound] if the devices were enumerated again since this one was found
+    /// and it is no longer at the same index, i.e. because a device was plugged in or removed.
+    fn open_enumerated(&self, config: Option<&Config>) -> Result<DeviceHandle, WaveFormsError> {
+        if get_string!(FDwfEnumSN self.index).ok().as_deref() != Some(self.serial_number.as_str()) {
+            return Err(WaveFormsError {
+                reason: format!(
+                    "device with serial number {:?} moved since it was enumerated",
+                    self.serial_number
+                ),
+                error_code: WaveFormsErrorCode::NotFound,
+            });
+        }
         // TODO: libdwf doesn't actually return the correct error
         // for this, overriding their logic here.
         if get_bool!(FDwfEnumDeviceIsOpened self.index)? {
@@ -146,69 +306,236 @@ impl Device {
                 error_code: WaveFormsErrorCode::AlreadyOpened,
             });
         }
-        let handle = get_int!(FDwfDeviceOpen self.index)?;
+        let handle = match config {
+            Some(config) => get_int!(FDwfDeviceConfigOpen self.index, config.index)?,
+            None => get_int!(FDwfDeviceOpen self.index)?,
+        };
         Ok(DeviceHandle {
             handle: Some(handle),
+            serial_number: self.serial_number.clone(),
+            device_type: Some(self.ty),
+            config: config.cloned(),
         })
     }
 }
 
 /// Detect and iterate over found [Device]s
+///
+/// # Panics
+///
+/// If the SDK fails to enumerate the devices. Use [try_iter_devices] to handle that instead.
 pub fn iter_devices() -> impl Iterator<Item = Device> {
+    try_iter_devices().unwrap()
+}
+
+/// Like [iter_devices], but returns an error if the SDK fails to enumerate the devices
+pub fn try_iter_devices() -> Result<impl Iterator<Item = Device>, WaveFormsError> {
+    let _enumeration = lock_enumeration();
+    enumerate().map(Vec::into_iter)
+}
+
+/// Serializes enumerating and opening devices across threads.
+///
+/// `FDwfEnum` replaces the device list that [Device::open] indexes into,
+/// so another thread enumerating in between could make it open the wrong device.
+static ENUMERATION: Mutex<()> = Mutex::new(());
+
+fn lock_enumeration() -> MutexGuard<'static, ()> {
+    // Nothing is guarded, so a panic while it was held leaves nothing inconsistent
+    ENUMERATION
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Enumerate the devices, which must be done with the [ENUMERATION] lock held
+fn enumerate() -> Result<Vec<Device>, WaveFormsError> {
     use core::convert::TryFrom;
-    let device_count = get_int!(FDwfEnum DetectFilter::All.into()).unwrap();
-    (0..device_count).map(|device_index| {
-        let mut version = 0;
-        let id = get_int!(FDwfEnumDeviceType device_index, &mut version).unwrap();
-
-        let config_count = get_int!(FDwfEnumConfig device_index).unwrap();
-        let configs = (0..config_count)
-            .map(|config_index| Config {
-                index: config_index,
-                analog: DomainConfig {
-                    input_channels:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIAnalogInChannelCount).unwrap()
-                            as u32,
-                    output_channels:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIAnalogOutChannelCount)
-                            .unwrap() as u32,
-                    io_channels: get_int!(FDwfEnumConfigInfo config_index, DECIAnalogIOChannelCount)
-                        .unwrap() as u32,
-                    input_buffer_size:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIAnalogInBufferSize).unwrap()
-                            as u32,
-                    output_buffer_size:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIAnalogOutBufferSize).unwrap()
-                            as u32,
-                },
-                digital: DomainConfig {
-                    input_channels:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIDigitalInChannelCount)
-                            .unwrap() as u32,
-                    output_channels:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIDigitalOutChannelCount)
-                            .unwrap() as u32,
-                    io_channels:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIDigitalIOChannelCount)
-                            .unwrap() as u32,
-                    input_buffer_size:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIDigitalInBufferSize).unwrap()
-                            as u32,
-                    output_buffer_size:
-                        get_int!(FDwfEnumConfigInfo config_index, DECIDigitalOutBufferSize).unwrap()
-                            as u32,
-                },
+    let device_count = get_int!(FDwfEnum DetectFilter::All.into())?;
+    (0..device_count)
+        .map(|device_index| {
+            let mut version = 0;
+            let id = get_int!(FDwfEnumDeviceType device_index, &mut version)?;
+
+            let config_count = get_int!(FDwfEnumConfig device_index)?;
+            let configs = (0..config_count)
+                .map(|config_index| {
+                    let info = |info| {
+                        get_int!(FDwfEnumConfigInfo config_index, info).map(|value| value as u32)
+                    };
+                    Ok(Config {
+                        index: config_index,
+                        analog: DomainConfig {
+                            input_channels: info(DECIAnalogInChannelCount)?,
+                            output_channels: info(DECIAnalogOutChannelCount)?,
+                            io_channels: info(DECIAnalogIOChannelCount)?,
+                            input_buffer_size: info(DECIAnalogInBufferSize)?,
+                            output_buffer_size: info(DECIAnalogOutBufferSize)?,
+                        },
+                        digital: DomainConfig {
+                            input_channels: info(DECIDigitalInChannelCount)?,
+                            output_channels: info(DECIDigitalOutChannelCount)?,
+                            io_channels: info(DECIDigitalIOChannelCount)?,
+                            input_buffer_size: info(DECIDigitalInBufferSize)?,
+                            output_buffer_size: info(DECIDigitalOutBufferSize)?,
+                        },
+                    })
+                })
+                .collect::<Result<Vec<_>, WaveFormsError>>()?;
+
+            Ok(Device {
+                index: device_index,
+                ty: DeviceType::try_from(id)?,
+                username: get_string!(FDwfEnumUserName device_index)?,
+                name: get_string!(FDwfEnumDeviceName device_index)?,
+                serial_number: get_string!(FDwfEnumSN device_index)?,
+                configs,
             })
-            .collect::<Vec<_>>();
-
-        Device {
-            index: device_index,
-            ty: DeviceType::try_from(id).unwrap(),
-            username: get_string!(FDwfEnumUserName device_index).unwrap(),
-            name: get_string!(FDwfEnumDeviceName device_index).unwrap(),
-            serial_number: get_string!(FDwfEnumSN device_index).unwrap(),
-            configs,
+        })
+        .collect()
+}
+
+/// Open the device with this serial number, i.e. one reported by a [watcher::DeviceWatcher].
+///
+/// The devices are enumerated again and the device is opened before any other thread can enumerate.
+/// Fails with [WaveFormsErrorCode::NotFound] if no attached device has this serial number.
+pub fn open_by_serial_number(serial_number: &str) -> Result<DeviceHandle, WaveFormsError> {
+    let _enumeration = lock_enumeration();
+    enumerate()?
+        .into_iter()
+        .find(|device| device.serial_number == serial_number)
+        .ok_or_else(|| WaveFormsError {
+            reason: format!(
+                "device with serial number {:?} was not found",
+                serial_number
+            ),
+            error_code: WaveFormsErrorCode::NotFound,
+        })?
+        .open_enumerated(None)
+}
+
+/// Open the first device for which `predicate` is true.
+///
+/// i.e. the first Analog Discovery 2 with at least a 16k sample scope buffer:
+/// ```no_run
+/// # use waveforms_sdk::*;
+/// # fn main() -> Result<(), WaveFormsError> {
+/// let handle = open_matching(|device| {
+///     device.device_type() == DeviceType::AnalogDiscovery2
+///         && device
+///             .configs()
+///             .iter()
+///             .any(|config| config.analog.input_buffer_size >= 16384)
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Fails with [WaveFormsErrorCode::NotFound] if no device matches.
+pub fn open_matching(predicate: impl Fn(&Device) -> bool) -> Result<DeviceHandle, WaveFormsError> {
+    iter_devices()
+        .find(|device| predicate(device))
+        .ok_or_else(|| WaveFormsError {
+            reason: "no device matched".to_owned(),
+            error_code: WaveFormsErrorCode::NotFound,
+        })?
+        .open()
+}
+
+/// Minimum capabilities for [open_with_capability]. Fields left at 0 are not required.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilityRequest {
+    pub scope_channels: u32,
+    /// Oscilloscope buffer size in samples
+    pub scope_buffer_size: u32,
+    pub awg_channels: u32,
+    /// Waveform generator custom data buffer size in samples
+    pub awg_buffer_size: u32,
+    pub logic_channels: u32,
+    /// Logic analyzer buffer size in samples
+    pub logic_buffer_size: u32,
+    pub pattern_channels: u32,
+    /// Pattern generator buffer size in bits per channel
+    pub pattern_buffer_size: u32,
+}
+
+impl CapabilityRequest {
+    /// Whether a device opened with `config` has all of the requested capabilities
+    pub fn is_met_by(&self, config: &Config) -> bool {
+        config.analog.input_channels >= self.scope_channels
+            && config.analog.input_buffer_size >= self.scope_buffer_size
+            && config.analog.output_channels >= self.awg_channels
+            && config.analog.output_buffer_size >= self.awg_buffer_size
+            && config.digital.input_channels >= self.logic_channels
+            && config.digital.input_buffer_size >= self.logic_buffer_size
+            && config.digital.output_channels >= self.pattern_channels
+            && config.digital.output_buffer_size >= self.pattern_buffer_size
+    }
+}
+
+/// Open the first device allowed by `filter` that has a [Config] meeting `want`, with the first such config.
+///
+/// Devices that are already opened are skipped.
+/// Fails with [WaveFormsErrorCode::NotFound] if no device can be opened with the requested capabilities.
+/// ```no_run
+/// # use waveforms_sdk::*;
+/// # fn main() -> Result<(), WaveFormsError> {
+/// let handle = open_with_capability(
+///     DetectFilter::All,
+///     CapabilityRequest {
+///         scope_channels: 2,
+///         scope_buffer_size: 16384,
+///         ..Default::default()
+///     },
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn open_with_capability(
+    filter: DetectFilter,
+    want: CapabilityRequest,
+) -> Result<DeviceHandle, WaveFormsError> {
+    for device in iter_devices().filter(|device| filter.matches(device.device_type())) {
+        if let Some(config) = device
+            .configs()
+            .iter()
+            .find(|config| want.is_met_by(config))
+        {
+            match device.open_with_config(config) {
+                Err(WaveFormsError {
+                    error_code: WaveFormsErrorCode::AlreadyOpened,
+                    ..
+                }) => continue,
+                result => return result,
+            }
         }
+    }
+    Err(WaveFormsError {
+        reason: format!("no device can be opened with {:?}", want),
+        error_code: WaveFormsErrorCode::NotFound,
+    })
+}
+
+/// Open a device shared over the network by a computer running WaveForms, or a network-attached device like the Analog Discovery Pro.
+///
+/// `address` is an IP address or hostname. It is passed to the SDK as the connection string `ip:<address>`,
+/// and more options can be appended as newline separated `key:value` pairs, i.e. `"192.168.1.10\nuser:digilent\npass:digilent"`.
+///
+/// Remote devices are not listed by [iter_devices], and [DeviceHandle::reconnect] does not work for them.
+/// Needs the `remote` feature, as `FDwfDeviceOpenEx` is only exported by WaveForms SDK 3.16 or newer.
+#[cfg(feature = "remote")]
+pub fn open_remote(address: &str) -> Result<DeviceHandle, WaveFormsError> {
+    use std::ffi::CString;
+    let options = CString::new(format!("ip:{}", address)).map_err(|_| WaveFormsError {
+        reason: "address must not contain a nul character".to_owned(),
+        error_code: WaveFormsErrorCode::InvalidParameter(0),
+    })?;
+    let handle = get_int!(FDwfDeviceOpenEx options.as_ptr())?;
+    Ok(DeviceHandle {
+        handle: Some(handle),
+        serial_number: String::new(),
+        device_type: None,
+        config: None,
     })
 }
 
@@ -223,6 +550,19 @@ enum_only! {
     }
 }
 
+impl DetectFilter {
+    /// Whether the filter allows devices of type `ty`
+    pub fn matches(self, ty: DeviceType) -> bool {
+        match self {
+            DetectFilter::All => true,
+            DetectFilter::ElectronicsExplorer => ty == DeviceType::ElectronicsExplorer,
+            DetectFilter::AnalogDiscovery => ty == DeviceType::AnalogDiscovery,
+            DetectFilter::AnalogDiscovery2 => ty == DeviceType::AnalogDiscovery2,
+            DetectFilter::DigitalDiscovery => ty == DeviceType::DigitalDiscovery,
+        }
+    }
+}
+
 enum_only! {
     DeviceType c_int {
         ElectronicsExplorer => devidEExplorer,
@@ -253,13 +593,117 @@ make_struct! {
     }
 }
 
+make_struct! {
+    /// Largest buffers that can be used by each instrument at the same time with the opened [Config].
+    ///
+    /// Buffer memory is shared between instruments, so these depend on the config chosen with [Device::open_with_config].
+    /// Sizes are in samples, except for the pattern generator which is in bits per channel.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    ResourceLimits {
+        oscilloscope_buffer_size: usize,
+        waveform_generator_buffer_size: usize,
+        logic_analyzer_buffer_size: usize,
+        pattern_generator_buffer_size: usize
+    }
+}
+
+/// More trigger pins than any device has, to stop [DeviceHandle::trigger_matrix] if the SDK never rejects a pin
+const MAX_TRIGGER_PINS: usize = 16;
+
+make_struct! {
+    /// The trigger source of each trigger pin, from [DeviceHandle::trigger_matrix]. Pin `i` has source `pins[i]`.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    TriggerMatrix {
+        pins: Vec<TriggerSource>
+    }
+}
+
+make_struct! {
+    /// State of every instrument from [DeviceHandle::states].
+    ///
+    /// Instruments the device does not have are [None], and `waveform_generator` has the state of each channel.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    InstrumentStates {
+        oscilloscope: Option<InstrumentState>,
+        waveform_generator: Vec<InstrumentState>,
+        logic_analyzer: Option<InstrumentState>,
+        pattern_generator: Option<InstrumentState>
+    }
+}
+
 #[derive(Debug)]
 /// Exclusive lock on a device
 pub struct DeviceHandle {
     handle: Option<c_int>,
+    serial_number: String,
+    /// Unknown for remote devices
+    device_type: Option<DeviceType>,
+    config: Option<Config>,
 }
 
 impl DeviceHandle {
+    /// Index of the [Config] this handle was opened with.
+    ///
+    /// This is only known if the device was opened with [Device::open_with_config].
+    pub fn opened_config_index(&self) -> Option<u32> {
+        self.config.as_ref().map(|config| config.index as u32)
+    }
+
+    /// The [Config] this handle was opened with.
+    ///
+    /// This is only known if the device was opened with [Device::open_with_config].
+    pub fn opened_config(&self) -> Option<&Config> {
+        self.config.as_ref()
+    }
+
+    /// Channel counts and buffer sizes of the analog domain in the opened [Config]
+    pub fn analog_limits(&self) -> Option<&DomainConfig> {
+        self.config.as_ref().map(|config| &config.analog)
+    }
+
+    /// Channel counts and buffer sizes of the digital domain in the opened [Config]
+    pub fn digital_limits(&self) -> Option<&DomainConfig> {
+        self.config.as_ref().map(|config| &config.digital)
+    }
+
+    /// Buffer sizes available to each instrument when they are used together.
+    ///
+    /// Instruments that the device does not have are reported with a size of zero.
+    pub fn resource_limits(&self) -> Result<ResourceLimits, WaveFormsError> {
+        use std::convert::TryFrom;
+        let handle = self.handle.unwrap();
+        let mut min = 0;
+        let mut max = 0;
+        let oscilloscope_buffer_size = if get_int!(FDwfAnalogInChannelCount handle)? > 0 {
+            call!(FDwfAnalogInBufferSizeInfo handle, &mut min, &mut max)?;
+            usize::try_from(max).unwrap_or(0)
+        } else {
+            0
+        };
+        let waveform_generator_buffer_size = if get_int!(FDwfAnalogOutCount handle)? > 0 {
+            call!(FDwfAnalogOutNodeDataInfo handle, 0, AnalogOutNodeCarrier, &mut min, &mut max)?;
+            usize::try_from(max).unwrap_or(0)
+        } else {
+            0
+        };
+        let logic_analyzer_buffer_size = if get_int!(FDwfDigitalInBitsInfo handle)? > 0 {
+            usize::try_from(get_int!(FDwfDigitalInBufferSizeInfo handle)?).unwrap_or(0)
+        } else {
+            0
+        };
+        let pattern_generator_buffer_size = if get_int!(FDwfDigitalOutCount handle)? > 0 {
+            usize::try_from(get_int!(FDwfDigitalOutDataInfo handle, 0)?).unwrap_or(0)
+        } else {
+            0
+        };
+        Ok(ResourceLimits {
+            oscilloscope_buffer_size,
+            waveform_generator_buffer_size,
+            logic_analyzer_buffer_size,
+            pattern_generator_buffer_size,
+        })
+    }
+
     /// Returns the supported trigger source options for the global trigger bus.
     pub fn trigger_sources(&self) -> Result<SupportedTriggerSources, WaveFormsError> {
         Ok(SupportedTriggerSources::from(
@@ -267,6 +711,14 @@ impl DeviceHandle {
         ))
     }
 
+    /// Trigger slopes the device reports for its trigger pins.
+    ///
+    /// The SDK has no setting for the polarity or pulse width of a trigger pin used as an output,
+    /// which follows the [TriggerSource] routed to it with [DeviceHandle::set_trigger].
+    pub fn trigger_slopes(&self) -> Result<SupportedSamplingSlopes, WaveFormsError> {
+        get_int!(FDwfDeviceTriggerSlopeInfo self.handle.unwrap()).map(SupportedSamplingSlopes::from)
+    }
+
     pub fn get_trigger(&self, pin_index: u32) -> Result<TriggerSource, WaveFormsError> {
         use core::convert::TryFrom;
         get_int!(FDwfDeviceTriggerGet self.handle.unwrap(), pin_index as c_int).and_then(TriggerSource::try_from)
@@ -280,6 +732,31 @@ impl DeviceHandle {
         call!(FDwfDeviceTriggerSet self.handle.unwrap(), pin_index as c_int, src.into())
     }
 
+    /// Read the source of every trigger pin.
+    ///
+    /// The SDK does not report how many trigger pins there are, so pins are read until one is rejected.
+    pub fn trigger_matrix(&self) -> Result<TriggerMatrix, WaveFormsError> {
+        let mut pins = vec![];
+        while pins.len() < MAX_TRIGGER_PINS {
+            match self.get_trigger(pins.len() as u32) {
+                Ok(source) => pins.push(source),
+                Err(err) if matches!(err.error_code, WaveFormsErrorCode::UnknownVariant) => {
+                    return Err(err)
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(TriggerMatrix { pins })
+    }
+
+    /// Set the source of every trigger pin in `matrix`, i.e. to restore one read with [DeviceHandle::trigger_matrix]
+    pub fn apply_trigger_matrix(&mut self, matrix: &TriggerMatrix) -> Result<(), WaveFormsError> {
+        for (pin_index, source) in matrix.pins.iter().enumerate() {
+            self.set_trigger(pin_index as u32, *source)?;
+        }
+        Ok(())
+    }
+
     /// Generate one pulse on the PC trigger line.
     ///
     /// This can be used to trigger multiple instruments synchronously.
@@ -287,12 +764,64 @@ impl DeviceHandle {
         call!(FDwfDeviceTriggerPC self.handle.unwrap())
     }
 
+    /// Start instruments in lockstep by pulsing the PC trigger line.
+    ///
+    /// Every instrument that should start together must first have its trigger source set to
+    /// [TriggerSource::Pc] and be started, so that it is [InstrumentState::Armed] and waiting for the pulse.
+    ///
+    /// ```no_run
+    /// # use waveforms_sdk::*;
+    /// # fn main() -> Result<(), WaveFormsError> {
+    /// let mut handle = iter_devices().next().unwrap().open()?;
+    /// for mut channel in handle.waveform_generator()?.channels()? {
+    ///     channel.set_trigger_source(TriggerSource::Pc)?;
+    ///     channel.start()?;
+    /// }
+    /// let mut pattern_generator = handle.pattern_generator()?;
+    /// pattern_generator.set_trigger_source(TriggerSource::Pc)?;
+    /// pattern_generator.start()?;
+    /// handle.fire_synchronized()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fire_synchronized(&mut self) -> Result<(), WaveFormsError> {
+        self.trigger_pc()
+    }
+
+    /// Estimate the USB round trip latency to the device as the median time of `samples` status reads.
+    ///
+    /// Timestamps taken on this computer can be offset by this to correlate captures across multiple devices.
+    pub fn measure_round_trip_latency(
+        &mut self,
+        samples: usize,
+    ) -> Result<Duration, WaveFormsError> {
+        if samples == 0 {
+            return Err(WaveFormsError {
+                reason: "at least one sample is needed".to_owned(),
+                error_code: WaveFormsErrorCode::InvalidParameter(0),
+            });
+        }
+        let mut latencies = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let start = Instant::now();
+            call!(FDwfAnalogIOStatus self.handle.unwrap())?;
+            latencies.push(start.elapsed());
+        }
+        latencies.sort();
+        Ok(latencies[samples / 2])
+    }
+
     /// Analog in
     pub fn oscilloscope<'handle>(
         &'handle mut self,
     ) -> Result<Oscilloscope<'handle>, WaveFormsError> {
+        require_channels(
+            get_int!(FDwfAnalogInChannelCount self.handle.unwrap())?,
+            "oscilloscope",
+        )?;
         Ok(Oscilloscope {
             device_handle: self.handle.unwrap(),
+            enabled_cache: None,
             phantom: std::marker::PhantomData,
         })
     }
@@ -301,16 +830,50 @@ impl DeviceHandle {
     pub fn waveform_generator<'handle>(
         &'handle mut self,
     ) -> Result<WaveformGenerator<'handle>, WaveFormsError> {
+        require_channels(
+            get_int!(FDwfAnalogOutCount self.handle.unwrap())?,
+            "waveform generator",
+        )?;
         Ok(WaveformGenerator {
             device_handle: self.handle.unwrap(),
             phantom: std::marker::PhantomData,
         })
     }
 
+    /// Analog I/O, i.e. power supplies
+    pub fn analog_io<'handle>(&'handle mut self) -> Result<AnalogIo<'handle>, WaveFormsError> {
+        Ok(AnalogIo {
+            device_handle: self.handle.unwrap(),
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Impedance analyzer, which uses the analog in and out instruments
+    pub fn impedance_analyzer<'handle>(
+        &'handle mut self,
+    ) -> Result<ImpedanceAnalyzer<'handle>, WaveFormsError> {
+        require_channels(
+            get_int!(FDwfAnalogInChannelCount self.handle.unwrap())?
+                .min(get_int!(FDwfAnalogOutCount self.handle.unwrap())?),
+            "impedance analyzer",
+        )?;
+        Ok(ImpedanceAnalyzer {
+            device_handle: self.handle.unwrap(),
+            open: None,
+            short: None,
+            load_correction: 1.,
+            phantom: std::marker::PhantomData,
+        })
+    }
+
     /// Digital in
     pub fn logic_analyzer<'handle>(
         &'handle mut self,
     ) -> Result<LogicAnalyzer<'handle>, WaveFormsError> {
+        require_channels(
+            get_int!(FDwfDigitalInBitsInfo self.handle.unwrap())?,
+            "logic analyzer",
+        )?;
         Ok(LogicAnalyzer {
             device_handle: self.handle.unwrap(),
             phantom: std::marker::PhantomData,
@@ -321,6 +884,10 @@ impl DeviceHandle {
     pub fn pattern_generator<'handle>(
         &'handle mut self,
     ) -> Result<PatternGenerator<'handle>, WaveFormsError> {
+        require_channels(
+            get_int!(FDwfDigitalOutCount self.handle.unwrap())?,
+            "pattern generator",
+        )?;
         Ok(PatternGenerator {
             device_handle: self.handle.unwrap(),
             phantom: std::marker::PhantomData,
@@ -335,6 +902,162 @@ impl DeviceHandle {
         })
     }
 
+    /// Close a stale handle and reopen the same device, i.e. after a USB disconnect.
+    ///
+    /// The device is found again by its serial number and opened with the same [Config] if it is known.
+    ///
+    /// All instrument configuration is lost and must be reapplied after reconnecting.
+    /// If the device can't be found, the current handle is kept. If it is found but can't be reopened,
+    /// the stale handle is still closed, so the error is returned and the handle must not be used until a reconnect succeeds.
+    /// Handles from `open_remote` have no serial number, so they fail with [WaveFormsErrorCode::NotFound].
+    pub fn reconnect(&mut self) -> Result<(), WaveFormsError> {
+        let _enumeration = lock_enumeration();
+        self.find_by_serial_number()?;
+        // Closing a stale handle is expected to fail
+        let _ = self.close_ref();
+        // Enumerate again so the device is no longer reported as opened by the stale handle
+        let device = self.find_by_serial_number()?;
+        let mut reopened = device.open_enumerated(self.config.as_ref())?;
+        self.handle = reopened.handle.take();
+        Ok(())
+    }
+
+    /// Enumerate and find this device, which must be done with the [ENUMERATION] lock held
+    fn find_by_serial_number(&self) -> Result<Device, WaveFormsError> {
+        enumerate()?
+            .into_iter()
+            .find(|device| {
+                !self.serial_number.is_empty() && device.serial_number == self.serial_number
+            })
+            .ok_or_else(|| WaveFormsError {
+                reason: format!(
+                    "device with serial number {:?} was not found",
+                    self.serial_number
+                ),
+                error_code: WaveFormsErrorCode::NotFound,
+            })
+    }
+
+    /// What happens to the device when the handle is closed
+    pub fn set_on_close(&mut self, on_close: OnClose) -> Result<(), WaveFormsError> {
+        call!(FDwfDeviceParamSet self.handle.unwrap(), DwfParamOnClose, on_close.into())
+    }
+
+    pub fn get_on_close(&self) -> Result<OnClose, WaveFormsError> {
+        use core::convert::TryFrom;
+        get_int!(FDwfDeviceParamGet self.handle.unwrap(), DwfParamOnClose)
+            .and_then(OnClose::try_from)
+    }
+
+    /// Whether the device keeps drawing power from USB when the AUX supply is connected.
+    ///
+    /// Disable this when powering the device from AUX for more current, so that it doesn't backfeed USB.
+    /// Fails with [WaveFormsErrorCode::NotSupported] on devices without an AUX input, which is only the Analog Discovery 2.
+    pub fn set_keep_usb_power(&mut self, keep: bool) -> Result<(), WaveFormsError> {
+        self.require_aux_power()?;
+        call!(FDwfDeviceParamSet self.handle.unwrap(), DwfParamUsbPower, keep as c_int)
+    }
+
+    pub fn get_keep_usb_power(&self) -> Result<bool, WaveFormsError> {
+        self.require_aux_power()?;
+        get_int!(FDwfDeviceParamGet self.handle.unwrap(), DwfParamUsbPower).map(|keep| keep != 0)
+    }
+
+    fn require_aux_power(&self) -> Result<(), WaveFormsError> {
+        match self.device_type {
+            Some(device_type) if device_type != DeviceType::AnalogDiscovery2 => {
+                Err(WaveFormsError {
+                    reason: format!("{:?} does not have an AUX power input", device_type),
+                    error_code: WaveFormsErrorCode::NotSupported,
+                })
+            }
+            // Remote devices can't be identified, so the SDK decides
+            _ => Ok(()),
+        }
+    }
+
+    /// When instrument settings are sent to the device
+    pub fn set_auto_configure(&mut self, mode: AutoConfigure) -> Result<(), WaveFormsError> {
+        mode.set(self.handle.unwrap())
+    }
+
+    pub fn get_auto_configure(&self) -> Result<AutoConfigure, WaveFormsError> {
+        AutoConfigure::get(self.handle.unwrap())
+    }
+
+    /// Change many settings at once with auto-configure disabled, then send them to the device together.
+    ///
+    /// The settings are applied by configuring the oscilloscope, waveform generator, and logic analyzer.
+    /// Stopped instruments stay stopped, and running oscilloscope and logic analyzer acquisitions are restarted with the new settings.
+    /// The pattern generator can't be configured without starting or stopping it, so its settings are applied when it is next started.
+    /// The previous auto-configure mode is restored afterwards, even if `f` fails.
+    pub fn batch_config(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), WaveFormsError>,
+    ) -> Result<(), WaveFormsError> {
+        let previous = self.get_auto_configure()?;
+        self.set_auto_configure(AutoConfigure::Disable)?;
+        let result = f(self).and_then(|_| self.configure_all());
+        let restore = self.set_auto_configure(previous);
+        result.and(restore)
+    }
+
+    /// Send settings to every instrument the device has without changing whether they are running
+    fn configure_all(&mut self) -> Result<(), WaveFormsError> {
+        let handle = self.handle.unwrap();
+        if get_int!(FDwfAnalogInChannelCount handle)? > 0 {
+            analog::scope::apply_settings(handle)?;
+        }
+        // Applies settings to all channels without starting or stopping them
+        if get_int!(FDwfAnalogOutCount handle)? > 0 {
+            call!(FDwfAnalogOutConfigure handle, -1, 3)?;
+        }
+        if get_int!(FDwfDigitalInBitsInfo handle)? > 0 {
+            digital::analyzer::apply_settings(handle)?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot of every instrument's state for diagnostics, i.e. to see which instrument a synchronized capture is stuck on.
+    ///
+    /// Only the states are read, no acquired data is transferred.
+    pub fn states(&self) -> Result<InstrumentStates, WaveFormsError> {
+        use std::convert::TryFrom;
+        let handle = self.handle.unwrap();
+        let oscilloscope = if get_int!(FDwfAnalogInChannelCount handle)? > 0 {
+            Some(get_int!(FDwfAnalogInStatus handle, 0).and_then(InstrumentState::try_from)?)
+        } else {
+            None
+        };
+        let waveform_generator = (0..get_int!(FDwfAnalogOutCount handle)?)
+            .map(|index| {
+                get_int!(FDwfAnalogOutStatus handle, index).and_then(InstrumentState::try_from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let logic_analyzer = if get_int!(FDwfDigitalInBitsInfo handle)? > 0 {
+            Some(get_int!(FDwfDigitalInStatus handle, 0).and_then(InstrumentState::try_from)?)
+        } else {
+            None
+        };
+        let pattern_generator = if get_int!(FDwfDigitalOutCount handle)? > 0 {
+            Some(get_int!(FDwfDigitalOutStatus handle).and_then(InstrumentState::try_from)?)
+        } else {
+            None
+        };
+        Ok(InstrumentStates {
+            oscilloscope,
+            waveform_generator,
+            logic_analyzer,
+            pattern_generator,
+        })
+    }
+
+    /// Close the handle but leave the instruments running, i.e. to keep a signal generated after the program exits.
+    pub fn detach(mut self) -> Result<(), WaveFormsError> {
+        self.set_on_close(OnClose::Continue)?;
+        self.close_ref()
+    }
+
     /// Close the handle when you are done using the device.
     ///
     /// This will be done on your behalf when the handle is dropped.
@@ -352,12 +1075,59 @@ impl DeviceHandle {
     }
 }
 
+/// Instruments without any channels in the opened [Config] are not supported, i.e. the oscilloscope of a Digital Discovery
+fn require_channels(channel_count: c_int, instrument: &str) -> Result<(), WaveFormsError> {
+    if channel_count > 0 {
+        Ok(())
+    } else {
+        Err(WaveFormsError {
+            reason: format!("device has no {} channels in this config", instrument),
+            error_code: WaveFormsErrorCode::NotSupported,
+        })
+    }
+}
+
 impl Drop for DeviceHandle {
     fn drop(&mut self) {
         self.close_ref().unwrap()
     }
 }
 
+enum_only! {
+    /// See [DeviceHandle::set_auto_configure]
+    AutoConfigure c_int {
+        /// Settings are only sent when an instrument is configured, i.e. [Oscilloscope::start]
+        Disable => 0,
+        /// Every setting is sent as soon as it is changed
+        Enable => 1,
+        /// Like [AutoConfigure::Enable], but settings are sent without resetting a running instrument when possible
+        Dynamic => 3
+    }
+}
+
+impl AutoConfigure {
+    pub(crate) fn get(handle: c_int) -> Result<Self, WaveFormsError> {
+        use core::convert::TryFrom;
+        get_int!(FDwfDeviceAutoConfigureGet handle).and_then(AutoConfigure::try_from)
+    }
+
+    pub(crate) fn set(self, handle: c_int) -> Result<(), WaveFormsError> {
+        call!(FDwfDeviceAutoConfigureSet handle, self.into())
+    }
+}
+
+enum_only! {
+    /// See [DeviceHandle::set_on_close]
+    OnClose c_int {
+        /// Instruments keep running
+        Continue => 0,
+        /// Instruments are stopped
+        Stop => 1,
+        /// Instruments are stopped and the device is powered down
+        Shutdown => 2
+    }
+}
+
 enum_and_support_bitfield! {
     /// Sources for the on-device global trigger bus.
     TriggerSource c_uchar {
@@ -373,6 +1143,8 @@ enum_and_support_bitfield! {
         /// When this is running
         DigitalIn => trigsrcDigitalIn,
         /// When this is running
+        DigitalOut => trigsrcDigitalOut,
+        /// When this is running
         AnalogOut1 => trigsrcAnalogOut1,
         /// When this is running
         AnalogOut2 => trigsrcAnalogOut2,
@@ -411,7 +1183,7 @@ enum_and_support_bitfield! {
         ScanShift => acqmodeScanShift,
         /// Perform continuous acquisition circularly writing samples into the buffer.
         ///
-        /// The trigger setting is ignored. The IndexWrite shows the buffer write position. This is similar to a heart monitor display.
+        /// The trigger setting is ignored. [Oscilloscope::write_index] shows the buffer write position. This is similar to a heart monitor display.
         ScanScreen => acqmodeScanScreen,
         /// Perform acquisition for defined record length. See [Oscilloscope::set_record_length] and [LogicAnalyzer::set_record_length]
         Record => acqmodeRecord,
@@ -421,6 +1193,10 @@ enum_and_support_bitfield! {
     }
 }
 
+kebab_case_strings! {
+    AcquisitionMode { Single, ScanShift, ScanScreen, Record, Overs, SingleWithoutRearm }
+}
+
 enum_only! {
     /// Possible states for all instruments.Each has a different state lifecycle.
     ///
@@ -472,3 +1248,113 @@ enum_only! {
         Wait => DwfStateWait
     }
 }
+
+impl InstrumentState {
+    /// Whether the instrument has been started and has not finished yet.
+    ///
+    /// This is true while the instrument is [InstrumentState::Armed], [InstrumentState::Running],
+    /// and in the intermediate [InstrumentState::Prefill] and [InstrumentState::Wait] states.
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self,
+            InstrumentState::Armed
+                | InstrumentState::Running
+                | InstrumentState::Prefill
+                | InstrumentState::Wait
+        )
+    }
+}
+
+/// Lifecycle methods shared by the [Oscilloscope], each [WaveformGenerator] channel, the [LogicAnalyzer], and the [PatternGenerator],
+/// for writing generic code that works with any of them.
+///
+/// Each instrument also has these as inherent methods, so the trait only needs to be imported for generic code.
+pub trait Instrument {
+    /// Reset the instrument's configuration to its defaults
+    fn reset(&mut self) -> Result<(), WaveFormsError>;
+
+    fn start(&mut self) -> Result<(), WaveFormsError>;
+
+    fn stop(&mut self) -> Result<(), WaveFormsError>;
+
+    /// Check the instrument state without reading data from the device
+    fn state(&self) -> Result<InstrumentState, WaveFormsError>;
+
+    /// Shorthand for [InstrumentState::is_running] on the current state
+    fn is_running(&self) -> Result<bool, WaveFormsError> {
+        self.state().map(|state| state.is_running())
+    }
+}
+
+impl<'handle> Instrument for Oscilloscope<'handle> {
+    fn reset(&mut self) -> Result<(), WaveFormsError> {
+        Oscilloscope::reset(self)
+    }
+
+    fn start(&mut self) -> Result<(), WaveFormsError> {
+        Oscilloscope::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), WaveFormsError> {
+        Oscilloscope::stop(self)
+    }
+
+    fn state(&self) -> Result<InstrumentState, WaveFormsError> {
+        Oscilloscope::state(self)
+    }
+}
+
+/// Waveform generator channels run independently, so each channel is an instrument
+impl<'handle> Instrument for analog::gen::Channel<'handle> {
+    fn reset(&mut self) -> Result<(), WaveFormsError> {
+        analog::gen::Channel::reset(self)
+    }
+
+    fn start(&mut self) -> Result<(), WaveFormsError> {
+        analog::gen::Channel::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), WaveFormsError> {
+        analog::gen::Channel::stop(self)
+    }
+
+    fn state(&self) -> Result<InstrumentState, WaveFormsError> {
+        analog::gen::Channel::state(self)
+    }
+}
+
+impl<'handle> Instrument for LogicAnalyzer<'handle> {
+    fn reset(&mut self) -> Result<(), WaveFormsError> {
+        LogicAnalyzer::reset(self)
+    }
+
+    fn start(&mut self) -> Result<(), WaveFormsError> {
+        LogicAnalyzer::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), WaveFormsError> {
+        LogicAnalyzer::stop(self)
+    }
+
+    fn state(&self) -> Result<InstrumentState, WaveFormsError> {
+        LogicAnalyzer::state(self)
+    }
+}
+
+impl<'handle> Instrument for PatternGenerator<'handle> {
+    fn reset(&mut self) -> Result<(), WaveFormsError> {
+        PatternGenerator::reset(self)
+    }
+
+    fn start(&mut self) -> Result<(), WaveFormsError> {
+        PatternGenerator::start(self)
+    }
+
+    fn stop(&mut self) -> Result<(), WaveFormsError> {
+        PatternGenerator::stop(self)
+    }
+
+    fn state(&self) -> Result<InstrumentState, WaveFormsError> {
+        PatternGenerator::state(self)
+    }
+}
@@ -73,7 +73,7 @@ macro_rules! call {
 
 macro_rules! make_struct {
     ($(#[$struct_meta:meta])* $name:ident { $($field:ident : $ty: ty),* }) => {
-        #[derive(Debug, PartialEq)]
+        #[derive(Debug, PartialEq, Clone)]
         $(#[$struct_meta])*
         pub struct $name {
             $(
@@ -92,6 +92,7 @@ macro_rules! enum_only {
     }) => {
         paste! {
             #[derive(Debug, PartialEq, Clone, Copy)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             $(#[$enum_meta])*
             #[non_exhaustive]
             pub enum $name {
@@ -168,6 +169,7 @@ macro_rules! enum_and_support_bitfield {
 
             #[non_exhaustive]
             #[derive(Debug, PartialEq, Clone, Copy)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             $(#[$enum_meta])*
             pub enum $name {
                 $(
@@ -200,6 +202,47 @@ macro_rules! enum_and_support_bitfield {
     };
 }
 
+/// Display and FromStr for an enum using its variant names in lowercase kebab-case, i.e. `RampUp` is `ramp-up`
+macro_rules! kebab_case_strings {
+    ($name: ident { $($field: ident),* }) => {
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                use std::fmt::Write;
+                let name = match self {
+                    $(Self::$field => stringify!($field),)*
+                };
+                for (i, c) in name.chars().enumerate() {
+                    if c.is_ascii_uppercase() && i != 0 {
+                        f.write_char('-')?;
+                    }
+                    f.write_char(c.to_ascii_lowercase())?;
+                }
+                Ok(())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = WaveFormsError;
+            fn from_str(s: &str) -> Result<Self, WaveFormsError> {
+                let variants = [$(Self::$field),*];
+                variants
+                    .iter()
+                    .find(|variant| variant.to_string() == s)
+                    .copied()
+                    .ok_or_else(|| crate::WaveFormsError {
+                        reason: format!(
+                            "`{}` is not a {}, expected one of {}",
+                            s,
+                            stringify!($name),
+                            variants.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+                        ),
+                        error_code: crate::WaveFormsErrorCode::InvalidParameter(0),
+                    })
+            }
+        }
+    };
+}
+
 macro_rules! enum_getter_and_setter {
     ($(#[$field_meta:meta])* $name: ident $ty: ident $base: ident $($arg: expr),*) => {
         paste! {
@@ -3,6 +3,315 @@ fn version() {
     dbg!(crate::version());
 }
 
+#[test]
+fn init() {
+    crate::init().unwrap();
+}
+
+#[test]
+fn tristate_bits() {
+    use crate::digital::gen::{pack_tristate_bits, unpack_tristate_bits};
+    let values = [true, false, true, true, false];
+    let enables = [true, true, false, true, false];
+    let bits = pack_tristate_bits(&values, &enables).unwrap();
+    assert_eq!(bits, vec![0b1101_1011, 0b0000_0000]);
+    assert_eq!(
+        unpack_tristate_bits(&bits, values.len()),
+        (values.to_vec(), enables.to_vec())
+    );
+    assert!(pack_tristate_bits(&values, &enables[1..]).is_err());
+}
+
+#[test]
+fn capability_request() {
+    use crate::{CapabilityRequest, Config, DomainConfig};
+    let config = Config {
+        index: 0,
+        analog: DomainConfig {
+            input_channels: 2,
+            output_channels: 2,
+            io_channels: 2,
+            input_buffer_size: 8192,
+            output_buffer_size: 4096,
+        },
+        digital: DomainConfig {
+            input_channels: 16,
+            output_channels: 16,
+            io_channels: 16,
+            input_buffer_size: 4096,
+            output_buffer_size: 1024,
+        },
+    };
+    assert!(CapabilityRequest::default().is_met_by(&config));
+    assert!(CapabilityRequest {
+        scope_channels: 2,
+        scope_buffer_size: 8192,
+        logic_channels: 16,
+        ..Default::default()
+    }
+    .is_met_by(&config));
+    assert!(!CapabilityRequest {
+        scope_buffer_size: 16384,
+        ..Default::default()
+    }
+    .is_met_by(&config));
+    assert!(!CapabilityRequest {
+        awg_channels: 3,
+        ..Default::default()
+    }
+    .is_met_by(&config));
+}
+
+#[test]
+fn cancel_token() {
+    let token = crate::CancelToken::new();
+    let other = token.clone();
+    assert!(!token.is_cancelled());
+    std::thread::spawn(move || other.cancel()).join().unwrap();
+    assert!(token.is_cancelled());
+    token.reset();
+    assert!(!token.is_cancelled());
+}
+
+#[test]
+fn poll_until() {
+    use crate::{poll_until, CancelToken, WaveFormsErrorCode};
+    use std::time::Duration;
+    let mut polls = 0;
+    poll_until(
+        &mut polls,
+        |polls| {
+            *polls += 1;
+            Ok(*polls == 3)
+        },
+        |_| Ok(()),
+        Duration::MAX,
+        None,
+        "not done",
+    )
+    .unwrap();
+    assert_eq!(polls, 3);
+
+    let err = poll_until(
+        &mut polls,
+        |_| Ok(false),
+        |_| Ok(()),
+        Duration::from_secs(0),
+        None,
+        "not done",
+    )
+    .unwrap_err();
+    assert!(matches!(err.error_code, WaveFormsErrorCode::Timeout));
+
+    let token = CancelToken::new();
+    token.cancel();
+    let mut stopped = false;
+    let err = poll_until(
+        &mut stopped,
+        |_| Ok(false),
+        |stopped| {
+            *stopped = true;
+            Ok(())
+        },
+        Duration::MAX,
+        Some(&token),
+        "not done",
+    )
+    .unwrap_err();
+    assert!(matches!(err.error_code, WaveFormsErrorCode::Interrupted));
+    assert!(stopped);
+}
+
+#[test]
+fn acquisition_time() {
+    use crate::analog::scope::AcquisitionTime;
+    use uom::si::time::second;
+    let earlier = AcquisitionTime {
+        seconds: 1_600_000_000,
+        tick_num: 75_000_000,
+        tick_den: 100_000_000,
+    };
+    let later = AcquisitionTime {
+        seconds: 1_600_000_001,
+        tick_num: 25_000_010,
+        tick_den: 100_000_000,
+    };
+    assert!((later.since(&earlier).get::<second>() - 0.500_000_1).abs() < 1e-12);
+    assert_eq!(earlier.to_time().get::<second>(), 1_600_000_000.75);
+    let no_ticks = AcquisitionTime {
+        seconds: 5,
+        tick_num: 3,
+        tick_den: 0,
+    };
+    assert_eq!(no_ticks.to_time().get::<second>(), 5.);
+}
+
+#[test]
+fn code_to_volts() {
+    use crate::analog::scope::code_to_volts;
+    assert_eq!(code_to_volts(0, 5., 0.), 0.);
+    assert_eq!(code_to_volts(16384, 5., 0.), 1.25);
+    assert_eq!(code_to_volts(-32768, 5., 0.), -2.5);
+    assert_eq!(code_to_volts(8192, 5., 1.), -0.375);
+}
+
+#[test]
+fn clip_fraction() {
+    use crate::analog::scope::clip_fraction;
+    let samples = [-2.5, -1., 0., 1., 2.4995, 2.5, 3.];
+    assert_eq!(clip_fraction(&samples, -2.5, 2.5, 0.001), 4. / 7.);
+    assert_eq!(clip_fraction(&samples[1..4], -2.5, 2.5, 0.001), 0.);
+    assert_eq!(clip_fraction(&[], -2.5, 2.5, 0.001), 0.);
+}
+
+#[test]
+fn measure_duty_cycle() {
+    use crate::analog::measure::measure_duty_cycle;
+    let samples = [0., 1., 1., 1., 0., 1., 1., 1., 0., 1.];
+    assert_eq!(measure_duty_cycle(&samples, 0.5), Some(0.75));
+    assert_eq!(measure_duty_cycle(&samples[..6], 0.5), Some(0.75));
+    assert_eq!(measure_duty_cycle(&samples[..4], 0.5), None);
+}
+
+#[test]
+fn measure_rise_time() {
+    use crate::analog::measure::measure_rise_time;
+    use uom::si::{f64::Frequency, frequency::hertz, time::second};
+    let samples = [0., 0., 0., 0.25, 0.5, 0.75, 1., 1., 1.];
+    let rise_time = measure_rise_time(&samples, Frequency::new::<hertz>(2.), 10., 90.).unwrap();
+    assert!((rise_time.get::<second>() - 1.6).abs() < 1e-9);
+    assert!(measure_rise_time(&[1.; 4], Frequency::new::<hertz>(2.), 10., 90.).is_none());
+    assert!(measure_rise_time(&samples, Frequency::new::<hertz>(2.), 90., 10.).is_none());
+}
+
+#[test]
+fn spectrum() {
+    use crate::analog::spectrum::{spectrum, SpectrumConfig, SpectrumUnit, Window};
+    use std::f64::consts::PI;
+    use uom::si::{f64::Frequency, frequency::hertz};
+    // 1 V peak sine sampled at 64 Hz for one second, so bins are 1 Hz apart
+    let frame = |frequency: f64| {
+        (0..64)
+            .map(|n| (2. * PI * frequency * n as f64 / 64.).sin())
+            .collect::<Vec<_>>()
+    };
+    let config = SpectrumConfig {
+        window: Window::Rectangular,
+        averages: 1,
+        unit: SpectrumUnit::Linear,
+    };
+    let result = spectrum(&[frame(8.)], Frequency::new::<hertz>(64.), &config).unwrap();
+    assert_eq!(result.bin_width, Frequency::new::<hertz>(1.));
+    assert_eq!(result.magnitudes.len(), 33);
+    assert!((result.magnitudes[8] - 0.5f64.sqrt()).abs() < 1e-9);
+    assert!(result.magnitudes[3] < 1e-9);
+    let config = SpectrumConfig {
+        window: Window::FlatTop,
+        ..config
+    };
+    let result = spectrum(
+        &[frame(8.5), frame(8.5)],
+        Frequency::new::<hertz>(64.),
+        &config,
+    )
+    .unwrap();
+    let peak = result.magnitudes.iter().cloned().fold(0., f64::max);
+    assert!((peak - 0.5f64.sqrt()).abs() < 0.01);
+    assert!(spectrum(&[], Frequency::new::<hertz>(64.), &config).is_none());
+}
+
+#[test]
+fn measure_tone() {
+    use crate::analog::measure::measure_tone;
+    use std::f64::consts::PI;
+    use uom::si::{f64::Frequency, frequency::hertz};
+    let samples = (0..100)
+        .map(|n| 0.5 * (2. * PI * 5. * n as f64 / 100. + PI / 4.).cos() + 0.1)
+        .collect::<Vec<_>>();
+    let (amplitude, phase) = measure_tone(
+        &samples,
+        Frequency::new::<hertz>(100.),
+        Frequency::new::<hertz>(5.),
+    )
+    .unwrap();
+    assert!((amplitude - 0.5).abs() < 1e-9);
+    assert!((phase - 45.).abs() < 1e-9);
+    assert!(measure_tone(
+        &[],
+        Frequency::new::<hertz>(1.),
+        Frequency::new::<hertz>(1.)
+    )
+    .is_none());
+}
+
+#[test]
+fn expand_transitions() {
+    use crate::digital::analyzer::expand_transitions;
+    assert_eq!(expand_transitions(&[], 3), vec![0, 0, 0]);
+    assert_eq!(
+        expand_transitions(&[(2, 0b01), (4, 0b11), (5, 0b10)], 7),
+        vec![0b01, 0b01, 0b01, 0b01, 0b11, 0b10, 0b10]
+    );
+    assert_eq!(expand_transitions(&[(0, 1), (10, 0)], 2), vec![1, 1]);
+}
+
+#[test]
+fn pin_trigger_masks() {
+    use crate::digital::analyzer::{pin_trigger_masks, PinTrigger};
+    assert_eq!(
+        pin_trigger_masks(&[
+            (0, PinTrigger::Low),
+            (3, PinTrigger::High),
+            (4, PinTrigger::Rising),
+            (4, PinTrigger::Falling),
+            (31, PinTrigger::Falling),
+        ])
+        .unwrap(),
+        (
+            0b1,
+            0b1000,
+            0b1_0000,
+            0b1000_0000_0000_0000_0000_0000_0001_0000
+        )
+    );
+    assert!(pin_trigger_masks(&[(1, PinTrigger::Low), (1, PinTrigger::High)]).is_err());
+    assert!(pin_trigger_masks(&[(1, PinTrigger::High), (1, PinTrigger::Rising)]).is_err());
+    assert!(pin_trigger_masks(&[(32, PinTrigger::Low)]).is_err());
+}
+
+#[test]
+fn value_trigger_masks() {
+    use crate::digital::analyzer::value_trigger_masks;
+    assert_eq!(value_trigger_masks(0xff, 0x3f).unwrap(), (0xc0, 0x3f));
+    assert_eq!(
+        value_trigger_masks(0xf0f0, 0x5050).unwrap(),
+        (0xa0a0, 0x5050)
+    );
+    assert_eq!(value_trigger_masks(0, 0).unwrap(), (0, 0));
+    assert!(value_trigger_masks(0xff, 0x100).is_err());
+}
+
+#[test]
+fn kebab_case_strings() {
+    use crate::analog::{gen::GeneratorFunction, scope::SamplingSlope};
+    use crate::AcquisitionMode;
+    assert_eq!(GeneratorFunction::RampUp.to_string(), "ramp-up");
+    assert_eq!(
+        "sine".parse::<GeneratorFunction>().unwrap(),
+        GeneratorFunction::Sine
+    );
+    assert_eq!(
+        AcquisitionMode::SingleWithoutRearm.to_string(),
+        "single-without-rearm"
+    );
+    assert_eq!(
+        "rise".parse::<SamplingSlope>().unwrap(),
+        SamplingSlope::Rise
+    );
+    assert!("Sine".parse::<GeneratorFunction>().is_err());
+    assert!("ramp".parse::<GeneratorFunction>().is_err());
+}
+
 #[cfg(feature = "local_tests")]
 /// These can only be run on a system with an attached device.
 /// They must be explicitly enabled
@@ -16,5 +325,296 @@ mod local_tests {
         dbg!(channels[0].range_steps());
         channels[0].offset_steps();
     }
+
+    #[test]
+    fn spi_loopback() {
+        use uom::si::{f64::Frequency, frequency::hertz};
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut protocols = handle.protocols().unwrap();
+        let mut spi = protocols.spi();
+        spi.reset().unwrap();
+        spi.set_frequency(Frequency::new::<hertz>(1e6)).unwrap();
+        spi.set_clock_pin(1).unwrap();
+        // MISO reads back what MOSI drives on the same pin
+        spi.set_data_pin(0, 2).unwrap();
+        spi.set_data_pin(1, 2).unwrap();
+        let tx = [0xA5, 0x00, 0xFF, 0x3C];
+        let mut rx = [0; 4];
+        spi.select(0, false).unwrap();
+        spi.write_read(&tx, &mut rx).unwrap();
+        spi.select(0, true).unwrap();
+        assert_eq!(tx, rx);
+    }
+
+    #[test]
+    fn scope_read_samples() {
+        use std::time::Duration;
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        let mut channels = scope.channels().unwrap();
+        channels[0].enable().unwrap();
+        scope.capture_single(Duration::from_secs(1)).unwrap();
+        let samples = channels[0].read_samples(100).unwrap();
+        assert_eq!(samples.len(), 100);
+        let buffer_size = scope.get_sample_buffer_size().unwrap();
+        assert!(channels[0].read_samples(buffer_size + 1).is_err());
+    }
+
+    #[test]
+    fn scope_read_samples_raw() {
+        use std::time::Duration;
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        let mut channels = scope.channels().unwrap();
+        channels[0].enable().unwrap();
+        scope.capture_single(Duration::from_secs(1)).unwrap();
+        let codes = channels[0].read_samples_raw(0, 100).unwrap();
+        assert_eq!(codes.len(), 100);
+        assert!(codes.iter().any(|&code| code != 0));
+    }
+
+    #[test]
+    fn scope_codes_to_volts() {
+        use std::time::Duration;
+        use uom::si::electric_potential::volt;
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        let mut channels = scope.channels().unwrap();
+        channels[0].enable().unwrap();
+        scope.capture_single(Duration::from_secs(1)).unwrap();
+        let step = scope.calibration_info().unwrap().channels[0].volts_per_code;
+        let volts = channels[0].read_samples(100).unwrap();
+        let codes = channels[0].read_samples_raw(0, 100).unwrap();
+        let converted = channels[0].codes_to_volts(&codes).unwrap();
+        for (volts, converted) in volts.iter().zip(&converted) {
+            assert!((volts.get::<volt>() - converted.get::<volt>()).abs() <= step);
+        }
+    }
+
+    #[test]
+    fn scope_read_noise() {
+        use crate::analog::scope::Filter;
+        use std::time::Duration;
+        use uom::si::{f64::Frequency, frequency::hertz};
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        let mut channels = scope.channels().unwrap();
+        channels[0].enable().unwrap();
+        channels[0].set_filter(Filter::MinMax).unwrap();
+        scope
+            .set_sampling_frequency(Frequency::new::<hertz>(1e6))
+            .unwrap();
+        scope.capture_single(Duration::from_secs(1)).unwrap();
+        let count = scope.get_noise_buffer_size().unwrap().min(100);
+        let noise = channels[0].read_noise(0, count).unwrap();
+        assert_eq!(noise.len(), count);
+        assert!(noise.iter().all(|(min, max)| min <= max));
+    }
+
+    #[test]
+    fn scope_record_status() {
+        use crate::AcquisitionMode;
+        use uom::si::{
+            f64::{Frequency, Time},
+            frequency::hertz,
+            time::second,
+        };
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        scope.channels().unwrap()[0].enable().unwrap();
+        scope.set_acquisition_mode(AcquisitionMode::Record).unwrap();
+        scope
+            .set_sampling_frequency(Frequency::new::<hertz>(1e5))
+            .unwrap();
+        scope.set_record_length(Time::new::<second>(1.)).unwrap();
+        scope.start().unwrap();
+        let mut available = 0;
+        for _ in 0..10 {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            scope.fetch().unwrap();
+            let status = dbg!(scope.record_status().unwrap());
+            available += status.available;
+            // Polling every 50 ms keeps up with 100 kHz
+            assert_eq!(status.lost, 0);
+        }
+        scope.stop().unwrap();
+        assert!(available > 0);
+    }
+
+    #[test]
+    fn scope_write_index() {
+        use crate::AcquisitionMode;
+        use uom::si::{f64::Frequency, frequency::hertz};
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        scope.channels().unwrap()[0].enable().unwrap();
+        scope
+            .set_acquisition_mode(AcquisitionMode::ScanScreen)
+            .unwrap();
+        scope
+            .set_sampling_frequency(Frequency::new::<hertz>(1e3))
+            .unwrap();
+        scope.start().unwrap();
+        scope.fetch().unwrap();
+        let first = scope.write_index().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        scope.fetch().unwrap();
+        let second = scope.write_index().unwrap();
+        scope.stop().unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn scope_was_auto_triggered() {
+        use crate::TriggerSource;
+        use std::time::Duration;
+        use uom::si::{
+            electric_potential::volt,
+            f64::{ElectricPotential, Time},
+            time::second,
+        };
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        scope.channels().unwrap()[0].enable().unwrap();
+        scope
+            .set_trigger_source(TriggerSource::DetectorAnalogIn)
+            .unwrap();
+        // Nothing is connected, so the level is never reached
+        scope
+            .set_trigger_level(ElectricPotential::new::<volt>(4.))
+            .unwrap();
+        scope
+            .set_trigger_auto_timeout(Time::new::<second>(0.01))
+            .unwrap();
+        scope.capture_single(Duration::from_secs(1)).unwrap();
+        assert!(scope.was_auto_triggered().unwrap());
+    }
+
+    #[test]
+    fn scope_capture_blocking() {
+        use crate::{CancelToken, TriggerSource, WaveFormsErrorCode};
+        use std::time::Duration;
+        use uom::si::{
+            electric_potential::volt,
+            f64::{ElectricPotential, Time},
+            time::second,
+        };
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        scope.channels().unwrap()[0].enable().unwrap();
+        scope.set_trigger_source(TriggerSource::None).unwrap();
+        scope.capture_blocking().unwrap();
+
+        // Nothing is connected, so the level is never reached and only the token ends the wait
+        scope
+            .set_trigger_source(TriggerSource::DetectorAnalogIn)
+            .unwrap();
+        scope
+            .set_trigger_level(ElectricPotential::new::<volt>(4.))
+            .unwrap();
+        scope
+            .set_trigger_auto_timeout(Time::new::<second>(0.))
+            .unwrap();
+        let token = CancelToken::new();
+        let canceller = token.clone();
+        let cancel = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let err = scope.capture_blocking_cancellable(&token).unwrap_err();
+        cancel.join().unwrap();
+        assert!(matches!(err.error_code, WaveFormsErrorCode::Interrupted));
+        assert!(!scope.is_running().unwrap());
+    }
+
+    #[test]
+    fn scope_record_one_channel() {
+        use crate::AcquisitionMode;
+        use uom::si::{f64::Frequency, frequency::hertz};
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        let mut channels = scope.channels().unwrap();
+        channels[0].enable().unwrap();
+        channels[1].disable().unwrap();
+        scope.set_acquisition_mode(AcquisitionMode::Record).unwrap();
+        scope
+            .set_sampling_frequency(Frequency::new::<hertz>(1e5))
+            .unwrap();
+        scope.start().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(scope.ring_reader(0, 10_000).poll().unwrap() > 0);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        // Channel 1 has no samples of its own rather than channel 0's
+        assert!(scope.ring_reader(1, 10_000).poll().is_err());
+        scope.stop().unwrap();
+    }
+
+    #[test]
+    fn scope_record_two_channels() {
+        use crate::AcquisitionMode;
+        use uom::si::{f64::Frequency, frequency::hertz};
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut scope = handle.oscilloscope().unwrap();
+        let mut channels = scope.channels().unwrap();
+        channels[0].enable().unwrap();
+        channels[1].enable().unwrap();
+        scope.set_acquisition_mode(AcquisitionMode::Record).unwrap();
+        scope
+            .set_sampling_frequency(Frequency::new::<hertz>(1e5))
+            .unwrap();
+        scope.start().unwrap();
+        let mut buffers = scope
+            .double_buffer(vec![
+                (0, vec![0.; 10_000], vec![0.; 10_000]),
+                (1, vec![0.; 10_000], vec![0.; 10_000]),
+            ])
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let fill = buffers.poll().unwrap();
+        assert!(!fill.is_empty());
+        // Each channel gets the same number of its own samples
+        assert_eq!(fill.channel(0).len(), fill.len());
+        assert_eq!(fill.channel(1).len(), fill.len());
+        drop(buffers);
+        scope.stop().unwrap();
+    }
+
+    #[test]
+    fn awg_enable() {
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut awg = handle.waveform_generator().unwrap();
+        let mut channels = awg.channels().unwrap();
+        channels[0].enable().unwrap();
+        assert!(channels[0].is_enabled().unwrap());
+        channels[0].disable().unwrap();
+        assert!(!channels[0].is_enabled().unwrap());
+    }
+
+    #[test]
+    fn awg_frequency() {
+        use uom::si::{f64::Frequency, frequency::hertz};
+        let dev = &mut crate::iter_devices().collect::<Vec<_>>()[0];
+        let mut handle = dev.open().unwrap();
+        let mut awg = handle.waveform_generator().unwrap();
+        let mut channels = awg.channels().unwrap();
+        let range = channels[0].frequency_range().unwrap();
+        let frequency = Frequency::new::<hertz>(1e3);
+        assert!(range.contains(&frequency));
+        channels[0].set_frequency(frequency).unwrap();
+        let actual = channels[0].get_frequency().unwrap().get::<hertz>();
+        assert!((actual - 1e3).abs() < 1.);
+    }
     
 }
@@ -0,0 +1,111 @@
+use crate::*;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A change in the set of attached devices, see [DeviceWatcher]
+#[derive(Debug)]
+pub enum DeviceEvent {
+    /// The device with this serial number was plugged in, open it with [open_by_serial_number]
+    Added(String),
+    /// The device with this serial number was removed
+    Removed(String),
+}
+
+/// Watches for devices being plugged in or removed by periodically calling [try_iter_devices] on a background thread.
+///
+/// Enumerating does not affect devices that are already open. Events only carry serial numbers,
+/// as a [Device] found on the background thread may be at another index by the time it is opened,
+/// so use [open_by_serial_number] to enumerate and open it on the caller's thread.
+/// A failed enumeration is skipped and tried again on the next poll.
+/// A change is only reported once two enumerations in a row agree on it, so a device that briefly
+/// drops out while being plugged in is not reported twice.
+///
+/// The background thread stops when the watcher is dropped.
+#[derive(Debug)]
+pub struct DeviceWatcher {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DeviceWatcher {
+    /// Start watching, enumerating once every `poll_interval`.
+    ///
+    /// Devices that are already attached are reported as [DeviceEvent::Added] right away.
+    pub fn new(poll_interval: Duration) -> (Self, Receiver<DeviceEvent>) {
+        let (stop, stop_rx) = mpsc::channel();
+        let (events, events_rx) = mpsc::channel();
+        let thread = thread::spawn(move || watch(poll_interval, &stop_rx, &events));
+        (
+            Self {
+                stop: Some(stop),
+                thread: Some(thread),
+            },
+            events_rx,
+        )
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        // Disconnecting the channel wakes the thread up
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn watch(poll_interval: Duration, stop: &Receiver<()>, events: &Sender<DeviceEvent>) {
+    let serial_numbers = || {
+        try_iter_devices().map(|devices| {
+            devices
+                .map(|device| device.serial_number)
+                .collect::<HashSet<_>>()
+        })
+    };
+    let mut known = serial_numbers().unwrap_or_default();
+    for serial_number in &known {
+        if events
+            .send(DeviceEvent::Added(serial_number.clone()))
+            .is_err()
+        {
+            return;
+        }
+    }
+    let mut previous = known.clone();
+    while let Err(RecvTimeoutError::Timeout) = stop.recv_timeout(poll_interval) {
+        let current = match serial_numbers() {
+            Ok(current) => current,
+            Err(_) => continue,
+        };
+        let removed = known
+            .iter()
+            .filter(|serial_number| {
+                !current.contains(*serial_number) && !previous.contains(*serial_number)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for serial_number in removed {
+            known.remove(&serial_number);
+            if events.send(DeviceEvent::Removed(serial_number)).is_err() {
+                return;
+            }
+        }
+        let added = current
+            .iter()
+            .filter(|serial_number| {
+                !known.contains(*serial_number) && previous.contains(*serial_number)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        previous = current;
+        for serial_number in added {
+            known.insert(serial_number.clone());
+            if events.send(DeviceEvent::Added(serial_number)).is_err() {
+                return;
+            }
+        }
+    }
+}